@@ -0,0 +1,57 @@
+//! Times every variant of the days that keep letters in a `LetterSet` against their older ones, on
+//! large generated inputs. Run with `cargo bench --bench letters`, or time one day on an input
+//! from a file with `cargo bench --bench letters -- 6 target/day_6_large.txt`.
+
+use std::{
+    env, fs,
+    time::{Duration, Instant},
+};
+
+use advent_of_code_2022::{gen, rng::Rng, variants_for};
+
+/// The days to time, and how big an input to make up for each.
+const DAYS: [(u8, usize); 2] = [(3, 300_000), (6, 20_000_000)];
+
+/// How many times to run each variant, keeping the fastest run.
+const RUNS: usize = 3;
+
+fn main() {
+    // Cargo passes `--bench` along, which is not for us
+    let args: Vec<String> = env::args()
+        .skip(1)
+        .filter(|arg| !arg.starts_with("--"))
+        .collect();
+
+    let inputs: Vec<(u8, String)> = match &args[..] {
+        [] => DAYS
+            .iter()
+            .map(|&(day, size)| (day, gen::input(day, &mut Rng::new(1), size).unwrap()))
+            .collect(),
+        [day, path] => vec![(day.parse().unwrap(), fs::read_to_string(path).unwrap())],
+        _ => panic!("expected no arguments, or a day and the path to its input"),
+    };
+
+    for (day, input) in inputs {
+        let mut answers = None;
+
+        for variant in variants_for(day) {
+            let mut fastest = Duration::MAX;
+            for _ in 0..RUNS {
+                let input = input.clone();
+                let start = Instant::now();
+                let solved = (variant.solution)(input).unwrap();
+                fastest = fastest.min(start.elapsed());
+
+                // Every variant has to get the same answers for the timings to mean anything
+                assert_eq!(
+                    *answers.get_or_insert_with(|| solved.clone()),
+                    solved,
+                    "day {day} {} disagrees",
+                    variant.name
+                );
+            }
+
+            println!("day {day} {:<10} {fastest:?}", variant.name);
+        }
+    }
+}
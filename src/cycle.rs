@@ -0,0 +1,313 @@
+use std::{collections::HashMap, hash::Hash};
+
+/// Where a sequence of states starts repeating: the state after `start + length` steps is the
+/// same as the one after `start`, and so on forever.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Cycle {
+    /// The number of steps before the cycle is entered.
+    pub start: usize,
+    /// The number of steps around the cycle.
+    pub length: usize,
+}
+
+impl Cycle {
+    /// The earliest number of steps that reaches the same state as `n` steps do.
+    pub fn earliest(&self, n: usize) -> usize {
+        if n < self.start {
+            n
+        } else {
+            self.start + (n - self.start) % self.length
+        }
+    }
+
+    /// The value after `n` steps of something that changes by the same amount every time round the
+    /// cycle, like a count, given its value after each of the first `start + length` steps. `None`
+    /// if it does not fit.
+    fn extrapolate(&self, n: usize, mut value: impl FnMut(usize) -> i64) -> Option<i64> {
+        if n < self.start {
+            return Some(value(n));
+        }
+
+        let cycles = i64::try_from((n - self.start) / self.length).ok()?;
+        let per_cycle = value(self.start + self.length).checked_sub(value(self.start))?;
+        cycles
+            .checked_mul(per_cycle)?
+            .checked_add(value(self.earliest(n)))
+    }
+}
+
+/// Finds the cycle that stepping from `initial` falls into with Brent's algorithm, which only
+/// keeps two states at a time but steps through the sequence a few times over. States with the
+/// same `key` must go on to the same states, so the key has to cover everything that decides what
+/// comes next. Never returns if the states never repeat.
+pub fn brent<S: Clone, K: PartialEq>(
+    initial: &S,
+    mut step: impl FnMut(&S) -> S,
+    mut key: impl FnMut(&S) -> K,
+) -> Cycle {
+    // Find the length by leaving the tortoise at powers of two until the hare catches up with it
+    let (mut power, mut length) = (1, 1);
+    let mut tortoise = initial.clone();
+    let mut hare = step(initial);
+    while key(&tortoise) != key(&hare) {
+        if power == length {
+            tortoise = hare.clone();
+            power *= 2;
+            length = 0;
+        }
+        hare = step(&hare);
+        length += 1;
+    }
+
+    // Then find the start by racing from the beginning with the hare a whole cycle ahead
+    let mut tortoise = initial.clone();
+    let mut hare = initial.clone();
+    for _ in 0..length {
+        hare = step(&hare);
+    }
+    let mut start = 0;
+    while key(&tortoise) != key(&hare) {
+        tortoise = step(&tortoise);
+        hare = step(&hare);
+        start += 1;
+    }
+
+    Cycle { start, length }
+}
+
+/// The states from `initial` up to where they start repeating, remembered so that any later state
+/// can be looked up without stepping to it.
+#[derive(Debug, Clone)]
+pub struct History<S> {
+    /// Every state up to and including the first repeat, starting with the initial one.
+    states: Vec<S>,
+    pub cycle: Cycle,
+}
+
+impl<S> History<S> {
+    /// The earliest state with the same key as the state after `n` steps. Only the parts of it
+    /// that go into the key are sure to be right, so anything else that changes around the cycle,
+    /// like a count of laps, has to be worked out with [`History::extrapolate`].
+    pub fn nth(&self, n: usize) -> &S {
+        &self.states[self.cycle.earliest(n)]
+    }
+
+    /// The `value` of the state after `n` steps, for a value that changes by the same amount
+    /// every time round the cycle. `None` if it does not fit.
+    pub fn extrapolate(&self, n: usize, mut value: impl FnMut(&S) -> i64) -> Option<i64> {
+        self.cycle.extrapolate(n, |i| value(&self.states[i]))
+    }
+}
+
+/// Finds the cycle that stepping from `initial` falls into by remembering the key of every state
+/// until one comes round again, which steps through the sequence only once. States with the same
+/// `key` must go on to the same states. Never returns if the states never repeat.
+pub fn detect<S, K: Hash + Eq>(
+    initial: S,
+    mut step: impl FnMut(&S) -> S,
+    mut key: impl FnMut(&S) -> K,
+) -> History<S> {
+    let mut seen = HashMap::new();
+    let mut states = vec![];
+    let mut state = initial;
+
+    loop {
+        if let Some(&start) = seen.get(&key(&state)) {
+            let cycle = Cycle {
+                start,
+                length: states.len() - start,
+            };
+            states.push(state);
+            return History { states, cycle };
+        }
+
+        seen.insert(key(&state), states.len());
+        let next = step(&state);
+        states.push(state);
+        state = next;
+    }
+}
+
+/// The state after `n` steps from `initial`, skipping ahead once the states start repeating.
+/// States with the same `key` must go on to the same states, and only the parts of the state that
+/// go into the key are sure to be right: anything else is as it was a whole number of cycles
+/// before. Use [`extrapolate`] for values that change around the cycle.
+pub fn nth<S, K: Hash + Eq>(
+    initial: S,
+    n: usize,
+    mut step: impl FnMut(&S) -> S,
+    mut key: impl FnMut(&S) -> K,
+) -> S {
+    let mut seen = HashMap::new();
+    let mut state = initial;
+
+    for i in 0..n {
+        if let Some(start) = seen.insert(key(&state), i) {
+            // The state after `n` steps is the one this far into the cycle, from here
+            let remaining = (n - i) % (i - start);
+            for _ in 0..remaining {
+                state = step(&state);
+            }
+            return state;
+        }
+        state = step(&state);
+    }
+
+    state
+}
+
+/// The `value` of the state after `n` steps from `initial`, for a value that changes by the same
+/// amount every time round the cycle, like a count of laps or the height of a tower. States with
+/// the same `key` must go on to the same states. `None` if the value does not fit.
+pub fn extrapolate<S, K: Hash + Eq>(
+    initial: S,
+    n: usize,
+    mut step: impl FnMut(&S) -> S,
+    mut key: impl FnMut(&S) -> K,
+    mut value: impl FnMut(&S) -> i64,
+) -> Option<i64> {
+    let mut seen = HashMap::new();
+    let mut values = vec![];
+    let mut state = initial;
+
+    for i in 0..n {
+        values.push(value(&state));
+        if let Some(start) = seen.insert(key(&state), i) {
+            let cycle = Cycle {
+                start,
+                length: i - start,
+            };
+            return cycle.extrapolate(n, |i| values[i]);
+        }
+        state = step(&state);
+    }
+
+    Some(value(&state))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A sequence that wanders a while before going round in circles.
+    fn next(x: &u64) -> u64 {
+        (x * x + 1) % 255
+    }
+
+    /// Finds the cycle by stepping and checking every state before, the slow way.
+    fn brute(initial: u64) -> Cycle {
+        let mut states = vec![initial];
+        loop {
+            let state = next(states.last().unwrap());
+            if let Some(start) = states.iter().position(|&seen| seen == state) {
+                return Cycle {
+                    start,
+                    length: states.len() - start,
+                };
+            }
+            states.push(state);
+        }
+    }
+
+    #[test]
+    fn find_cycles() {
+        for initial in 0..255 {
+            let expected = brute(initial);
+
+            assert_eq!(brent(&initial, next, |&x| x), expected, "from {initial}");
+            assert_eq!(
+                detect(initial, next, |&x| x).cycle,
+                expected,
+                "from {initial}"
+            );
+        }
+
+        // A state that is already in a loop of one
+        assert_eq!(
+            brent(&7, |&x| x, |&x| x),
+            Cycle {
+                start: 0,
+                length: 1
+            }
+        );
+    }
+
+    #[test]
+    fn skip_ahead() {
+        let stepped = |initial: u64, n: usize| (0..n).fold(initial, |x, _| next(&x));
+
+        for initial in [0, 3, 100] {
+            let history = detect(initial, next, |&x| x);
+            for n in [0, 1, 5, 17, 100, 1000] {
+                let expected = stepped(initial, n);
+                assert_eq!(*history.nth(n), expected, "{n} steps from {initial}");
+                assert_eq!(
+                    nth(initial, n, next, |&x| x),
+                    expected,
+                    "{n} steps from {initial}"
+                );
+            }
+            assert_eq!(
+                *history.nth(1_000_000_000_000),
+                nth(initial, 1_000_000_000_000, next, |&x| x)
+            );
+        }
+    }
+
+    #[test]
+    fn keys() {
+        // Which lap a runner is on does not change where they go next, so it is left out of the key
+        let lap = |&(position, lap): &(u32, u32)| {
+            if position == 3 {
+                (0, lap + 1)
+            } else {
+                (position + 1, lap)
+            }
+        };
+
+        let cycle = brent(&(0, 0), lap, |&(position, _)| position);
+        assert_eq!(
+            cycle,
+            Cycle {
+                start: 0,
+                length: 4
+            }
+        );
+        assert_eq!(cycle.earliest(10), 2);
+
+        // Only the position is skipped ahead, so the lap has to be extrapolated
+        let position = |&(position, _): &(u32, u32)| position;
+        let laps = |&(_, lap): &(u32, u32)| i64::from(lap);
+        assert_eq!(nth((2, 0), 9, lap, position).0, 3);
+        assert_eq!(extrapolate((2, 0), 9, lap, position, laps), Some(2));
+
+        let history = detect((2, 0), lap, position);
+        assert_eq!(history.nth(9).0, 3);
+        assert_eq!(history.extrapolate(9, laps), Some(2));
+        assert_eq!(history.extrapolate(10, laps), Some(3));
+        assert_eq!(
+            history.extrapolate(usize::MAX, laps),
+            Some((usize::MAX / 4 + 1) as i64)
+        );
+    }
+
+    #[test]
+    fn extrapolate_counts() {
+        // Counting the steps taken is the same as counting them the slow way
+        let counted = |&(x, count): &(u64, i64)| (next(&x), count + 1);
+        for initial in [0, 3, 100] {
+            let history = detect((initial, 0), counted, |&(x, _)| x);
+            for n in [0, 1, 5, 17, 100, 1000] {
+                assert_eq!(history.extrapolate(n, |&(_, count)| count), Some(n as i64));
+                assert_eq!(
+                    extrapolate((initial, 0), n, counted, |&(x, _)| x, |&(_, count)| count),
+                    Some(n as i64)
+                );
+            }
+        }
+
+        let history = detect((0, 0), counted, |&(x, _)| x);
+        let trillions = |&(_, count): &(u64, i64)| count * 1_000_000_000_000;
+        assert_eq!(history.extrapolate(usize::MAX, trillions), None);
+    }
+}
@@ -1,49 +1,117 @@
-pub fn main(input: String) -> anyhow::Result<()> {
-    const ELF_DELIMITER: &str = "\n\n";
-    const ITEM_DELIMITER: char = '\n';
+use std::io::BufRead;
 
-    // Last elf has a trailing newline
-    let input = input.trim();
+use crate::{parsing::paragraphs, Answers};
 
-    let elves: Vec<_> = input
-        .split(ELF_DELIMITER)
-        .map(|elf_calories| {
-            let items = elf_calories
-                .split(ITEM_DELIMITER)
-                .map(|item_calories| item_calories.parse().unwrap_or_default())
-                .collect();
-
-            Elf { items }
-        })
-        .collect();
+pub fn main(input: String) -> anyhow::Result<Answers> {
+    let elves = parse(&input);
 
     let mut calorie_totals: Vec<_> = elves.iter().map(|elf| elf.total()).collect();
 
     calorie_totals.sort();
     calorie_totals.reverse();
 
+    Ok(answers(&calorie_totals))
+}
+
+/// Works like [`main`], but reads the items a line at a time and keeps only the three largest
+/// totals, so the whole list never has to be in memory.
+pub fn main_stream(input: &mut dyn BufRead) -> anyhow::Result<Answers> {
+    // The largest totals so far, largest first
+    let mut top_3 = [0; 3];
+    let mut total = 0;
+    let mut line = String::new();
+
+    loop {
+        line.clear();
+        let read = input.read_line(&mut line)?;
+
+        // A blank line or the end of the input finishes the elf
+        if read == 0 || line == "\n" {
+            if total > top_3[2] {
+                top_3[2] = total;
+                top_3.sort_unstable_by(|a, b| b.cmp(a));
+            }
+            total = 0;
+
+            if read == 0 {
+                break;
+            }
+            continue;
+        }
+
+        // Line endings are taken off like `str::lines` does
+        let item_calories = line
+            .strip_suffix("\r\n")
+            .or_else(|| line.strip_suffix('\n'))
+            .unwrap_or(&line);
+        let item: Item = item_calories.parse().unwrap_or_default();
+        total += u64::from(item);
+    }
+
+    Ok(answers(&top_3))
+}
+
+/// The answers from the calorie totals of the elves, largest first.
+fn answers(calorie_totals: &[u64]) -> Answers {
     // Part 1
-    let most_calories: u32 = calorie_totals.iter().take(1).sum();
+    let most_calories: u64 = calorie_totals.iter().take(1).sum();
     log::info!("most calories carried: {most_calories}");
 
     // Part 2
-    let top_3_calories: u32 = calorie_totals.iter().take(3).sum();
+    let top_3_calories: u64 = calorie_totals.iter().take(3).sum();
     log::info!("sum of top three calories carried: {top_3_calories}");
 
-    Ok(())
+    Answers::new(most_calories, top_3_calories)
+}
+
+/// Parses the items carried by each elf.
+pub(crate) fn parse(input: &str) -> Vec<Elf> {
+    paragraphs(input)
+        .map(|elf_calories| {
+            let items = elf_calories
+                .lines()
+                .map(|item_calories| item_calories.parse().unwrap_or_default())
+                .collect();
+
+            Elf { items }
+        })
+        .collect()
 }
 
 /// Item that contains calories.
 type Item = u32;
 
 /// Elf that carries items.
-struct Elf {
+pub(crate) struct Elf {
     items: Vec<Item>,
 }
 
 impl Elf {
     /// Returns the sum of calories carried by the elf.
-    fn total(&self) -> u32 {
-        self.items.iter().sum()
+    fn total(&self) -> u64 {
+        // Sum as u64 so that many large items cannot overflow
+        self.items.iter().map(|&item| u64::from(item)).sum()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::gen;
+
+    /// Adds up each elf's snacks and sorts the totals.
+    fn reference(input: &str) -> Answers {
+        let mut totals: Vec<u64> = input
+            .split("\n\n")
+            .map(|elf| elf.lines().map(|item| item.parse::<u64>().unwrap()).sum())
+            .collect();
+        totals.sort_unstable_by(|a, b| b.cmp(a));
+
+        Answers::new(totals[0], totals.iter().take(3).sum::<u64>())
+    }
+
+    #[test]
+    fn matches_reference() {
+        gen::assert_matches_reference(1, main, reference);
     }
 }
@@ -2,7 +2,15 @@ use std::fmt;
 
 use anyhow::bail;
 
-pub fn main(input: String) -> anyhow::Result<()> {
+use crate::{
+    explain::narrate,
+    grid::Grid,
+    ocr,
+    visualize::{self, Color, Frame, Visualize},
+    Answers,
+};
+
+pub fn main(input: String) -> anyhow::Result<Answers> {
     let instructions: Vec<_> = input
         .lines()
         .filter_map(|line| Instruction::try_from(line).ok())
@@ -13,25 +21,61 @@ pub fn main(input: String) -> anyhow::Result<()> {
 
     let mut crt_position = 0i64;
 
+    narrate!("Sprite position: {}\n", sprite(cpu.x));
+
     for instruction in instructions {
         let instruction_range = match instruction {
             Instruction::Noop => vec![None],
             Instruction::Addx(x) => vec![None, Some(x)],
         };
+        let cycles = instruction_range.len();
 
-        for inc_x in instruction_range {
+        for (i, inc_x) in instruction_range.into_iter().enumerate() {
             let sprite_position = cpu.x;
+            let cycle = cpu.cycle + 1;
+
+            if i == 0 {
+                narrate!("Start cycle {cycle:>3}: begin executing {instruction}");
+            }
+            narrate!("During cycle {cycle:>2}: CRT draws pixel in position {crt_position}");
+            if Cpu::MAGIC_CYCLES.contains(&cycle) {
+                narrate!(
+                    "During cycle {cycle:>2}: register X has the value {sprite_position}, so the signal strength is {cycle} * {sprite_position} = {}",
+                    cycle as i64 * sprite_position
+                );
+            }
 
             if (crt_position % 40).abs_diff(sprite_position) <= 1 {
                 let row = (crt_position / 40) as usize;
                 let col = (crt_position % 40) as usize;
 
-                let pixel = crt.0.get_mut(row).unwrap().get_mut(col).unwrap();
-                *pixel = '#';
+                // Programs running past the last pixel keep going without drawing anything
+                if let Some(pixel) = crt.0.get_mut((row, col)) {
+                    *pixel = '#';
+                }
             }
 
+            narrate!("Current CRT row: {}", crt.row_so_far(crt_position));
+            visualize::show(&Beam {
+                crt: &crt,
+                cycle,
+                position: crt_position,
+                sprite: sprite_position,
+            });
+
             crt_position += 1;
-            cpu.cycle(inc_x);
+            cpu.cycle(inc_x)?;
+
+            if i == cycles - 1 {
+                narrate!(
+                    "End of cycle {cycle:>2}: finish executing {instruction} (Register X is now {})",
+                    cpu.x
+                );
+                if inc_x.is_some() {
+                    narrate!("Sprite position: {}", sprite(cpu.x));
+                }
+                narrate!("");
+            }
         }
     }
 
@@ -39,19 +83,36 @@ pub fn main(input: String) -> anyhow::Result<()> {
     log::info!("part 1, sum of signal strengths: {}", cpu.state());
 
     // Part 2
-    log::info!("part 2, picture...");
-    println!("{crt}");
+    log::info!("part 2, picture...\n{crt}");
+    let letters = match ocr::read(&crt.0.map(|&pixel| pixel == '#')) {
+        Ok(letters) => letters,
+        Err(e) => {
+            // Not every program draws letters, so the picture is the answer then
+            log::warn!("failed to read the letters on the CRT: {e}");
+            crt.to_string()
+        }
+    };
+    log::info!("part 2, letters: {letters}");
 
-    Ok(())
+    Ok(Answers::new(cpu.state(), letters))
 }
 
 /// A CPU instruction.
-#[derive(Debug)]
-enum Instruction {
+#[derive(Debug, Clone, Copy)]
+pub(crate) enum Instruction {
     Noop,
     Addx(i64),
 }
 
+impl fmt::Display for Instruction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Instruction::Noop => write!(f, "noop"),
+            Instruction::Addx(x) => write!(f, "addx {x}"),
+        }
+    }
+}
+
 impl TryFrom<&str> for Instruction {
     type Error = anyhow::Error;
 
@@ -103,15 +164,25 @@ impl Cpu {
         }
     }
 
-    /// Performs one cycle of the CPU.
-    fn cycle(&mut self, increment_x: Option<i64>) {
+    /// Performs one cycle of the CPU. Fails if register X or the signal strength overflows.
+    fn cycle(&mut self, increment_x: Option<i64>) -> anyhow::Result<()> {
         self.cycle += 1;
         if Self::MAGIC_CYCLES.contains(&self.cycle) {
-            self.state += self.cycle as i64 * self.x;
+            let Some(state) = (self.cycle as i64)
+                .checked_mul(self.x)
+                .and_then(|strength| self.state.checked_add(strength))
+            else {
+                bail!("signal strength overflowed in cycle {}", self.cycle);
+            };
+            self.state = state;
         }
         if let Some(x) = increment_x {
-            self.x += x;
+            let Some(x) = self.x.checked_add(x) else {
+                bail!("register X overflowed in cycle {}", self.cycle);
+            };
+            self.x = x;
         }
+        Ok(())
     }
 
     /// Retrieves the final state calculation of the CPU.
@@ -122,23 +193,214 @@ impl Cpu {
 
 /// The CRT of the communication system.
 #[derive(Debug)]
-struct Crt([[char; 40]; 6]);
+struct Crt(Grid<char>);
 
 impl Crt {
     /// Creates a new CRT with all pixels initially off.
     fn new() -> Crt {
-        Crt([['.'; 40]; 6])
+        Crt(Grid::new(40, 6, '.'))
+    }
+
+    /// The pixels of the row holding `position`, up to and including it.
+    fn row_so_far(&self, position: i64) -> String {
+        let row = (position / 40) as usize;
+        let col = (position % 40) as usize;
+
+        self.0
+            .row(row)
+            .map(|row| row[..=col].iter().collect())
+            .unwrap_or_default()
     }
 }
 
+/// The CRT as its beam draws a pixel.
+struct Beam<'a> {
+    crt: &'a Crt,
+    cycle: u64,
+    /// The position of the pixel being drawn.
+    position: i64,
+    /// The middle of the sprite.
+    sprite: i64,
+}
+
+impl Visualize for Beam<'_> {
+    fn frame(&self) -> Frame {
+        let mut frame = Frame::from_text(format!("Cycle {}", self.cycle), &self.crt.to_string());
+        let row = (self.position / 40) as usize;
+
+        // The sprite lies on the row being drawn
+        for col in (self.sprite - 1..=self.sprite + 1).filter_map(|col| usize::try_from(col).ok()) {
+            frame.paint(col, row, Color::Green);
+        }
+        frame.paint((self.position % 40) as usize, row, Color::Red);
+
+        frame
+    }
+}
+
+/// Draws the sprite, three pixels wide and centred on `x`, on a row of the CRT.
+fn sprite(x: i64) -> String {
+    (0..40i64)
+        .map(|col| if col.abs_diff(x) <= 1 { '#' } else { '.' })
+        .collect()
+}
+
 impl fmt::Display for Crt {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        for row in self.0 {
-            for c in row {
-                write!(f, "{}", c)?;
+        write!(f, "{}", self.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::*;
+    use crate::{explain, gen, visualize};
+
+    /// Writes down register X during every cycle, then reads the answers off the list.
+    fn reference(input: &str) -> Answers {
+        let mut x = 1;
+        let mut during = vec![];
+
+        for line in input.lines() {
+            during.push(x);
+            if let Some(increment) = line.strip_prefix("addx ") {
+                during.push(x);
+                x += increment.parse::<i64>().unwrap();
             }
-            writeln!(f)?;
         }
-        Ok(())
+
+        let strength: i64 = [20, 60, 100, 140, 180, 220]
+            .iter()
+            .filter_map(|&cycle| during.get(cycle - 1).map(|x| cycle as i64 * x))
+            .sum();
+
+        let mut picture = String::new();
+        for pixel in 0..240 {
+            let lit = during
+                .get(pixel)
+                .is_some_and(|x| (pixel as i64 % 40 - x).abs() <= 1);
+            picture.push(if lit { '#' } else { '.' });
+            if pixel % 40 == 39 {
+                picture.push('\n');
+            }
+        }
+
+        Answers::new(strength, ocr::read_text(&picture).unwrap_or(picture))
+    }
+
+    #[test]
+    fn matches_reference() {
+        gen::assert_matches_reference(10, main, reference);
+    }
+
+    /// Writes a program that draws `picture`, 40 pixels wide and 6 high with `#` for lit pixels.
+    /// Register X only changes after an `addx`, which takes two cycles, so this searches for
+    /// values of X during each cycle that light the right pixels and only change after holding
+    /// for at least two cycles.
+    fn program_drawing(picture: &str) -> String {
+        const XS: std::ops::RangeInclusive<i64> = -5..=45;
+
+        let lit: Vec<bool> = picture
+            .lines()
+            .flat_map(|line| line.chars().map(|pixel| pixel == '#'))
+            .collect();
+        let fits = |cycle: usize, x: i64| (((cycle % 40) as i64).abs_diff(x) <= 1) == lit[cycle];
+
+        // The value X held during each cycle, whether it has held for two cycles yet, and the
+        // state in the cycle before, for every state that can draw the picture so far
+        let mut states = vec![HashMap::new(); lit.len()];
+        if fits(0, 1) {
+            states[0].insert((1, false), None);
+        }
+        for cycle in 1..lit.len() {
+            let previous: Vec<(i64, bool)> = states[cycle - 1].keys().copied().collect();
+            for (x, held) in previous {
+                let nexts = XS.filter(|&next| next == x || held);
+                for next in nexts.filter(|&next| fits(cycle, next)) {
+                    let state = (next, next == x);
+                    states[cycle].entry(state).or_insert(Some((x, held)));
+                }
+            }
+        }
+
+        let mut state = *states
+            .last()
+            .unwrap()
+            .keys()
+            .next()
+            .expect("cannot draw the picture");
+        let mut xs = vec![state.0];
+        for cycle in (1..lit.len()).rev() {
+            state = states[cycle][&state].unwrap();
+            xs.push(state.0);
+        }
+        xs.reverse();
+
+        let mut program = String::new();
+        let mut run = 1;
+        for pair in xs.windows(2) {
+            if pair[0] == pair[1] {
+                run += 1;
+            } else {
+                program += &"noop\n".repeat(run - 2);
+                program += &format!("addx {}\n", pair[1] - pair[0]);
+                run = 1;
+            }
+        }
+        program + &"noop\n".repeat(run)
+    }
+
+    #[test]
+    fn read_letters() {
+        let picture = "\
+            ###..#....###...##..####.###...##..#....\n\
+            #..#.#....#..#.#..#.#....#..#.#..#.#....\n\
+            #..#.#....#..#.#..#.###..###..#....#....\n\
+            ###..#....###..####.#....#..#.#....#....\n\
+            #....#....#....#..#.#....#..#.#..#.#....\n\
+            #....####.#....#..#.#....###...##..####.\n";
+
+        let answers = main(program_drawing(picture)).unwrap();
+        assert_eq!(answers.part2.as_deref(), Some("PLPAFBCL"));
+    }
+
+    #[test]
+    fn visualize_beam() {
+        let example = include_str!("../tests/examples/day10.txt");
+        let (answers, frames) = visualize::capture(|| main(example.to_string()));
+        let answers = answers.unwrap();
+
+        assert_eq!(frames.len(), 240);
+        assert_eq!(frames[0].title, "Cycle 1");
+        assert_eq!(frames[0].get(0, 0).unwrap().color, Some(Color::Red));
+        assert_eq!(frames[0].get(1, 0).unwrap().color, Some(Color::Green));
+
+        let last = frames.last().unwrap().to_string();
+        assert_eq!(
+            Some(last.split_once('\n').unwrap().1),
+            answers.part2.as_deref()
+        );
+    }
+
+    #[test]
+    fn explain_cycles() {
+        let example = include_str!("../tests/examples/day10.txt");
+        let (_, narrative) = explain::capture(|| main(example.to_string()));
+
+        assert!(narrative.starts_with(
+            "Sprite position: ###.....................................\n\n\
+             Start cycle   1: begin executing addx 15\n\
+             During cycle  1: CRT draws pixel in position 0\n\
+             Current CRT row: #\n"
+        ));
+        assert!(narrative.contains(
+            "End of cycle  2: finish executing addx 15 (Register X is now 16)\n\
+             Sprite position: ...............###......................\n"
+        ));
+        assert!(narrative.contains(
+            "During cycle 20: register X has the value 21, so the signal strength is 20 * 21 = 420\n"
+        ));
     }
 }
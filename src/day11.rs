@@ -1,134 +1,201 @@
-use std::collections::{HashMap, VecDeque};
+use std::{cmp::Reverse, collections::VecDeque};
 
 use anyhow::{anyhow, bail};
 
-/// The number of rounds to simulate.
+use crate::{
+    explain::narrate,
+    math::{add_mod, lcm, mul_mod},
+    parsing::{integers, key_value, paragraphs, Template},
+    Answers,
+};
+
+/// The number of rounds to simulate while worry levels are divided.
 const ROUNDS: usize = 20;
 
-/// The number by which to divide your worry level.
-const WORRY_LEVEL_DIVISOR: u32 = 3;
+/// The number of rounds to simulate once they no longer are.
+const LONG_ROUNDS: usize = 10_000;
 
-/// The number of monkeys expected in the input.
-const WANT_MONKEYS: usize = 4;
+/// The number by which to divide your worry level.
+const WORRY_LEVEL_DIVISOR: u64 = 3;
 
-pub fn main(input: String) -> anyhow::Result<()> {
-    let mut monkeys: Vec<Monkey> = input.split("\n\n").flat_map(Monkey::try_from).collect();
+pub fn main(input: String) -> anyhow::Result<Answers> {
+    let monkeys = paragraphs(&input)
+        .map(Monkey::try_from)
+        .collect::<anyhow::Result<Vec<_>>>()?;
 
-    let n = monkeys.len();
-    if n != WANT_MONKEYS {
-        bail!("want {WANT_MONKEYS} monkeys, got {n}");
+    if monkeys.is_empty() {
+        bail!("there are no monkeys in the notes");
     }
+    for (m, monkey) in monkeys.iter().enumerate() {
+        if monkey.number != m {
+            bail!("monkey {m} in the notes is numbered {}", monkey.number);
+        }
+    }
+
+    let part1 = monkey_business(monkeys.clone(), ROUNDS, Relief::Divide(WORRY_LEVEL_DIVISOR))?;
 
-    let mut throw_items: HashMap<usize, VecDeque<u32>> = HashMap::with_capacity(8);
+    // Every test only looks at the worry level modulo its divisor, so keeping worry levels modulo a
+    // multiple of every divisor throws items the same way
+    let modulus = monkeys
+        .iter()
+        .try_fold(1, |modulus, monkey| lcm(modulus, monkey.test_divisor))
+        .ok_or_else(|| anyhow!("the test divisors have no common multiple that fits"))?;
+    let part2 = monkey_business(monkeys, LONG_ROUNDS, Relief::Modulo(modulus))?;
 
-    for round in 0..ROUNDS {
-        log::info!("starting round {}", round + 1);
+    Ok(Answers::new(part1, part2))
+}
+
+/// How worry levels are kept from growing without end after each inspection.
+#[derive(Debug, Clone, Copy)]
+enum Relief {
+    /// Relief that the item is undamaged divides the worry level by this much.
+    Divide(u64),
+    /// Without relief, worry levels are kept modulo this multiple of every test divisor.
+    Modulo(u64),
+}
+
+/// Simulates `rounds` rounds of the monkeys throwing items, returning the level of monkey
+/// business: the product of the numbers of items inspected by the two busiest monkeys.
+fn monkey_business(mut monkeys: Vec<Monkey>, rounds: usize, relief: Relief) -> anyhow::Result<u64> {
+    // Inspections are only narrated one by one while there are few rounds
+    let detailed = matches!(relief, Relief::Divide(_));
+
+    let mut throw_items: Vec<VecDeque<u64>> = vec![VecDeque::new(); monkeys.len()];
+
+    for round in 0..rounds {
+        if detailed {
+            narrate!("== Round {} ==", round + 1);
+        }
 
         for (m, monkey) in monkeys.iter_mut().enumerate() {
-            if let Some(queue) = throw_items.get_mut(&m) {
-                while let Some(mut worry_level) = queue.pop_front() {
-                    let next_worry_level =
-                        (monkey.operation)(worry_level) / WORRY_LEVEL_DIVISOR % monkey.test_divisor;
-                    for i in 0..1000 {
-                        if (monkey.operation)(i) / WORRY_LEVEL_DIVISOR % monkey.test_divisor
-                            == next_worry_level
-                        {
-                            worry_level = i;
-                            break;
-                        }
-                    }
-                    monkey.items.push(worry_level);
-                }
+            // Items thrown to this monkey earlier in the round are inspected on its turn
+            monkey.items.extend(throw_items[m].drain(..));
+
+            if detailed {
+                narrate!("Monkey {m}:");
             }
 
             for item in &monkey.items {
-                // assert_eq!(
-                //     (monkey.operation)(*item) / WORRY_LEVEL_DIVISOR % monkey.test_divisor,
-                //     (monkey.modulo_operation)(*item, monkey.test_divisor) / WORRY_LEVEL_DIVISOR
-                //         % monkey.test_divisor,
-                //     "monkey {m} assertion failed: item={item}, WORRY_LEVEL_DIVISOR={WORRY_LEVEL_DIVISOR}, test_divisor={}", monkey.test_divisor
-                // );
-
-                let worry_level = (monkey.operation)(*item) / WORRY_LEVEL_DIVISOR;
+                let worry_level = match relief {
+                    Relief::Divide(divisor) => {
+                        narrate!("  Monkey {m} inspects an item with a worry level of {item}.");
+                        let inspected = monkey.inspect(*item)?;
+                        narrate!("    Worry level becomes {inspected}.");
+
+                        let worry_level = inspected / divisor;
+                        narrate!(
+                            "    Monkey gets bored with item. Worry level is divided by {divisor} to {worry_level}."
+                        );
+                        worry_level
+                    }
+                    Relief::Modulo(modulus) => monkey.operation.apply_mod(*item, modulus),
+                };
 
-                let to_monkey = if worry_level % monkey.test_divisor == 0 {
+                let divisible = worry_level.is_multiple_of(monkey.test_divisor);
+                let to_monkey = if divisible {
                     monkey.true_monkey
                 } else {
                     monkey.false_monkey
                 };
 
-                throw_items
-                    .entry(to_monkey)
-                    .and_modify(|queue| queue.push_back(worry_level))
-                    .or_insert_with(|| VecDeque::from([worry_level]));
+                if detailed {
+                    narrate!(
+                        "    Current worry level is {}divisible by {}.",
+                        if divisible { "" } else { "not " },
+                        monkey.test_divisor
+                    );
+                    narrate!(
+                        "    Item with worry level {worry_level} is thrown to monkey {to_monkey}."
+                    );
+                }
+
+                let Some(queue) = throw_items.get_mut(to_monkey) else {
+                    bail!("monkey {m} throws to monkey {to_monkey}, who is not there");
+                };
+                queue.push_back(worry_level);
             }
 
             monkey.inspected += monkey.items.len() as u64;
             monkey.items.clear();
         }
+
+        if detailed {
+            narrate!(
+                "After round {}, the monkeys are holding items with these worry levels:",
+                round + 1
+            );
+            for (m, queue) in throw_items.iter().enumerate() {
+                let held: Vec<String> = queue.iter().map(u64::to_string).collect();
+                narrate!("Monkey {m}: {}", held.join(", "));
+            }
+            narrate!("");
+        } else if [1, 20].contains(&(round + 1)) || (round + 1).is_multiple_of(1000) {
+            narrate!("== After round {} ==", round + 1);
+            for (m, monkey) in monkeys.iter().enumerate() {
+                narrate!("Monkey {m} inspected items {} times.", monkey.inspected);
+            }
+            narrate!("");
+        }
+    }
+
+    for monkey in &monkeys {
+        log::info!(
+            "monkey {} inspected {} items in {rounds} rounds",
+            monkey.number,
+            monkey.inspected
+        );
     }
 
-    monkeys.sort_unstable_by(|monkey1, monkey2| {
-        monkey2.inspected.partial_cmp(&monkey1.inspected).unwrap()
-    });
+    monkeys.sort_unstable_by_key(|monkey| Reverse(monkey.inspected));
 
     let monkey_business = monkeys
         .iter()
         .take(2)
         .fold(1, |acc, monkey| acc * monkey.inspected);
+    log::info!("level of monkey business after {rounds} rounds: {monkey_business}");
 
-    for (m, monkey) in monkeys.iter().enumerate() {
-        log::info!("monkey {m} inspected {} items", monkey.inspected);
-    }
-    log::info!("level of monkey business: {monkey_business}");
-
-    Ok(())
+    Ok(monkey_business)
 }
 
-/// Finds the result of modular division.
-fn mod_divide(a: u32, b: u32, m: u32) -> Option<u32> {
-    mod_inverse(b, m).map(|inv| inv * (a % m) % m)
+/// How a monkey changes your worry level when it inspects an item.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Operation {
+    Add(u64),
+    Multiply(u64),
+    Square,
 }
 
-/// Finds the modular inverse.
-fn mod_inverse(b: u32, m: u32) -> Option<u32> {
-    let (mut x, mut y) = (0, 0);
-    let gcd = gcd(b, m, &mut x, &mut y);
-
-    if gcd != 1 {
-        None
-    } else {
-        Some((x as u32 % m + m) % m)
+impl Operation {
+    /// The new worry level, or `None` if it overflows.
+    fn apply(self, old: u64) -> Option<u64> {
+        match self {
+            Operation::Add(n) => old.checked_add(n),
+            Operation::Multiply(n) => old.checked_mul(n),
+            Operation::Square => old.checked_mul(old),
+        }
     }
-}
 
-/// Finds the GCD.
-fn gcd(a: u32, b: u32, x: &mut i32, y: &mut i32) -> u32 {
-    if a == 0 {
-        (*x, *y) = (0, 1);
-        return b;
+    /// The new worry level modulo `m`.
+    fn apply_mod(self, old: u64, m: u64) -> u64 {
+        match self {
+            Operation::Add(n) => add_mod(old, n, m),
+            Operation::Multiply(n) => mul_mod(old, n, m),
+            Operation::Square => mul_mod(old, old, m),
+        }
     }
-
-    let (mut x1, mut y1) = (0, 0);
-    let gcd = gcd(b % a, a, &mut x1, &mut y1);
-
-    *x = y1 - (b as i32 / a as i32) * x1;
-    *y = x1;
-
-    gcd
 }
 
 /// A monkey that took your items.
-#[derive(Debug)]
-struct Monkey {
+#[derive(Debug, Clone)]
+pub(crate) struct Monkey {
+    /// The number the notes give the monkey, which should be its place among them.
+    number: usize,
     /// Items with a worry level.
-    items: Vec<u32>,
-    /// Operation to calculate the new worry level
-    operation: fn(u32) -> u32,
-    /// Modulo operation to calculate the new worry level
-    modulo_operation: fn(u32, u32) -> u32,
+    items: Vec<u64>,
+    /// Operation to calculate the new worry level.
+    operation: Operation,
     /// How the monkey decides where to throw the item next.
-    test_divisor: u32,
+    test_divisor: u64,
     /// Which monkey gets the item if the test is true.
     true_monkey: usize,
     /// Which monkey gets the item if the test is false.
@@ -137,96 +204,68 @@ struct Monkey {
     inspected: u64,
 }
 
+impl Monkey {
+    /// Inspects an item, returning its new worry level. Fails if the worry level overflows.
+    fn inspect(&self, worry_level: u64) -> anyhow::Result<u64> {
+        self.operation
+            .apply(worry_level)
+            .ok_or_else(|| anyhow!("worry level overflowed inspecting {worry_level}"))
+    }
+}
+
 impl TryFrom<&str> for Monkey {
     type Error = anyhow::Error;
 
     fn try_from(value: &str) -> Result<Self, Self::Error> {
-        let lines: Vec<_> = value.lines().collect();
-
-        let n: usize = lines
-            .first()
-            .and_then(|line| {
-                line.chars()
-                    .find(|c| c.is_ascii_digit())
-                    .and_then(|c| c.to_digit(10))
-            })
-            .ok_or_else(|| anyhow!("failed to get number for monkey"))?
-            as usize;
-
-        let items: Vec<u32> = lines
-            .get(1)
-            .and_then(|line| {
-                line.split_once(": ")
-                    .and_then(|(_, nums)| nums.split(", ").map(|n| n.parse().ok()).collect())
-            })
-            .ok_or_else(|| anyhow!("failed to get items for monkey"))?;
-
-        let operation = match n {
-            // test input
-            0 => |i| i * 19,
-            1 => |i| i + 6,
-            2 => |i| i * i,
-            3 => |i| i + 3,
-
-            // real input
-            // 0 => |i| i * 13,
-            // 1 => |i| i + 2,
-            // 2 => |i| i + 1,
-            // 3 => |i| i + 8,
-            // 4 => |i| i * i,
-            // 5 => |i| i + 4,
-            // 6 => |i| i * 17,
-            // 7 => |i| i + 5,
-            _ => bail!("failed to get operation for monkey"),
+        const NUMBER: Template = Template::new("Monkey {number}:");
+        const OPERATION: Template = Template::new("Operation: new = old {operator} {operand}");
+        const TEST: Template = Template::new("Test: divisible by {divisor}");
+        const IF_TRUE: Template = Template::new("If true: throw to monkey {monkey}");
+        const IF_FALSE: Template = Template::new("If false: throw to monkey {monkey}");
+
+        let mut lines = value.lines().map(str::trim);
+        let mut next_line = |what: &str| {
+            lines
+                .next()
+                .ok_or_else(|| anyhow!("failed to find the {what} of the monkey"))
         };
 
-        let modulo_operation = match n {
-            // test input
-            0 => |i, d| (i % d) * (19 % d),
-            1 => |i, d| (i % d) + (6 % d),
-            2 => |i, d| (i % d) * (i % d),
-            3 => |i, d| (i % d) + (3 % d),
-
-            // real input
-            // 0 => |i, d| (i % d) * (13 % d),
-            // 1 => |i, d| (i % d) + (2 % d),
-            // 2 => |i, d| (i % d) + (1 % d),
-            // 3 => |i, d| (i % d) + (8 % d),
-            // 4 => |i, d| (i % d) * (i % d),
-            // 5 => |i, d| (i % d) + (4 % d),
-            // 6 => |i, d| (i % d) * (17 % d),
-            // 7 => |i, d| (i % d) + (5 % d),
-            _ => bail!("failed to get operation for monkey"),
+        let number = NUMBER.captures(next_line("number")?)?.parse("number")?;
+
+        let (key, items) = key_value(next_line("items")?, ":")?;
+        if key != "Starting items" {
+            bail!("expected the starting items of monkey {number}, got {key}");
+        }
+        let items = integers(items)?;
+
+        let fields = OPERATION.captures(next_line("operation")?)?;
+        let operation = match (fields.get("operator"), fields.get("operand")) {
+            (Some("*"), Some("old")) => Operation::Square,
+            (Some("+"), Some("old")) => Operation::Multiply(2),
+            (Some("*"), _) => Operation::Multiply(fields.parse("operand")?),
+            (Some("+"), _) => Operation::Add(fields.parse("operand")?),
+            (operator, _) => bail!(
+                "unknown operator {} for monkey {number}",
+                operator.unwrap_or_default()
+            ),
         };
 
-        let test_divisor: u32 = lines
-            .get(3)
-            .and_then(|line| {
-                line.split_once("divisible by ")
-                    .and_then(|(_, divisor)| divisor.parse().ok())
-            })
-            .ok_or_else(|| anyhow!("failed to get test divisor for monkey"))?;
-
-        let true_monkey: usize = lines
-            .get(4)
-            .and_then(|line| {
-                line.split_once("true: throw to monkey ")
-                    .and_then(|(_, n)| n.parse().ok())
-            })
-            .ok_or_else(|| anyhow!("failed to get true monkey for monkey"))?;
-
-        let false_monkey: usize = lines
-            .get(5)
-            .and_then(|line| {
-                line.split_once("false: throw to monkey ")
-                    .and_then(|(_, n)| n.parse().ok())
-            })
-            .ok_or_else(|| anyhow!("failed to get false monkey for monkey"))?;
+        let test_divisor: u64 = TEST.captures(next_line("test")?)?.parse("divisor")?;
+        if test_divisor == 0 {
+            bail!("monkey {number} tests for divisibility by zero");
+        }
+
+        let true_monkey = IF_TRUE
+            .captures(next_line("true monkey")?)?
+            .parse("monkey")?;
+        let false_monkey = IF_FALSE
+            .captures(next_line("false monkey")?)?
+            .parse("monkey")?;
 
         Ok(Monkey {
+            number,
             items,
             operation,
-            modulo_operation,
             test_divisor,
             true_monkey,
             false_monkey,
@@ -238,10 +277,146 @@ impl TryFrom<&str> for Monkey {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::{explain, gen};
+
+    /// Reads the operations from the notes and passes items straight to other monkeys. Part 2
+    /// keeps worry levels modulo the product of the divisors. Gives `None` if a worry level
+    /// overflows.
+    fn reference(input: &str) -> Option<Answers> {
+        struct Notes {
+            /// The operands, `None` for the old worry level, and the operator.
+            operation: (Option<u64>, char, Option<u64>),
+            divisor: u64,
+            targets: [usize; 2],
+        }
+
+        let last_number = |line: &str| line.rsplit(' ').next().unwrap().parse().unwrap();
+        let mut starting_items: Vec<VecDeque<u64>> = vec![];
+        let mut notes = vec![];
+
+        for paragraph in input.split("\n\n") {
+            let lines: Vec<&str> = paragraph.lines().collect();
+            let (_, starting) = lines[1].split_once(": ").unwrap();
+            starting_items.push(starting.split(", ").map(|n| n.parse().unwrap()).collect());
+            let (_, operation) = lines[2].split_once("= ").unwrap();
+            let words: Vec<&str> = operation.split(' ').collect();
+            notes.push(Notes {
+                operation: (
+                    words[0].parse().ok(),
+                    words[1].chars().next().unwrap(),
+                    words[2].parse().ok(),
+                ),
+                divisor: last_number(lines[3]) as u64,
+                targets: [last_number(lines[4]), last_number(lines[5])],
+            });
+        }
+
+        let play = |rounds: usize, calm: &dyn Fn(u64) -> u64| {
+            let mut items = starting_items.clone();
+            let mut inspected = vec![0u64; notes.len()];
+
+            for _ in 0..rounds {
+                for m in 0..notes.len() {
+                    while let Some(old) = items[m].pop_front() {
+                        inspected[m] += 1;
+
+                        let (a, operator, b) = notes[m].operation;
+                        let (a, b) = (a.unwrap_or(old), b.unwrap_or(old));
+                        let new = calm(match operator {
+                            '*' => a.checked_mul(b)?,
+                            _ => a.checked_add(b)?,
+                        });
+
+                        let target =
+                            notes[m].targets[usize::from(!new.is_multiple_of(notes[m].divisor))];
+                        items[target].push_back(new);
+                    }
+                }
+            }
+
+            inspected.sort_unstable_by(|a, b| b.cmp(a));
+            Some(inspected[0] * inspected[1])
+        };
+
+        let product: u64 = notes.iter().map(|notes| notes.divisor).product();
+
+        Some(Answers::new(
+            play(ROUNDS, &|worry| worry / 3)?,
+            play(LONG_ROUNDS, &|worry| worry % product)?,
+        ))
+    }
+
+    #[test]
+    fn matches_reference() {
+        gen::assert_matches_reference(11, main, reference);
+    }
+
+    #[test]
+    fn overflow() {
+        let notes = "Monkey 0:\n  \
+                     Starting items: 99\n  \
+                     Operation: new = old * old\n  \
+                     Test: divisible by 2\n    \
+                     If true: throw to monkey 0\n    \
+                     If false: throw to monkey 0\n";
+
+        assert_eq!(reference(notes), None);
+        let error = main(notes.to_string()).unwrap_err();
+        assert!(error.to_string().contains("overflowed"), "{error}");
+    }
+
+    #[test]
+    fn parse_monkeys() {
+        let monkey = Monkey::try_from(
+            "Monkey 10:\n  \
+             Starting items: 54, 65, 75, 74\n  \
+             Operation: new = old + old\n  \
+             Test: divisible by 19\n    \
+             If true: throw to monkey 12\n    \
+             If false: throw to monkey 0",
+        )
+        .unwrap();
+
+        assert_eq!(monkey.number, 10);
+        assert_eq!(monkey.items, [54, 65, 75, 74]);
+        assert_eq!(monkey.operation, Operation::Multiply(2));
+        assert_eq!((monkey.true_monkey, monkey.false_monkey), (12, 0));
+
+        let error = Monkey::try_from("Monkey 1:\n  Starting items: 79\n  Operation: new = old * x")
+            .unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "invalid operand `x` at column 24 of `Operation: new = old * x`: invalid digit found in string"
+        );
+    }
 
     #[test]
-    fn test_mod_divide() {
-        let (a, b, m) = (8, 3, 5);
-        assert_eq!(mod_divide(a, b, m), Some(1));
+    fn explain_inspections() {
+        let example = include_str!("../tests/examples/day11.txt");
+        let (_, narrative) = explain::capture(|| main(example.to_string()));
+
+        assert!(narrative.starts_with(
+            "== Round 1 ==\n\
+             Monkey 0:\n  \
+             Monkey 0 inspects an item with a worry level of 79.\n    \
+             Worry level becomes 1501.\n    \
+             Monkey gets bored with item. Worry level is divided by 3 to 500.\n    \
+             Current worry level is not divisible by 23.\n    \
+             Item with worry level 500 is thrown to monkey 3.\n"
+        ));
+        assert!(narrative.contains(
+            "After round 1, the monkeys are holding items with these worry levels:\n\
+             Monkey 0: 20, 23, 27, 26\n\
+             Monkey 1: 2080, 25, 167, 207, 401, 1046\n\
+             Monkey 2: \n\
+             Monkey 3: \n"
+        ));
+        assert!(narrative.contains(
+            "== After round 20 ==\n\
+             Monkey 0 inspected items 99 times.\n\
+             Monkey 1 inspected items 97 times.\n\
+             Monkey 2 inspected items 8 times.\n\
+             Monkey 3 inspected items 103 times.\n"
+        ));
     }
 }
@@ -1,74 +1,120 @@
-use std::{collections::HashSet, fmt};
-
-pub fn main(input: String) -> anyhow::Result<()> {
-    let elevations: Vec<Vec<u8>> = input
-        .lines()
-        .map(|line| {
-            line.chars()
-                .filter_map(|c| match u8::try_from(c) {
-                    Ok(elevation) => Some(elevation),
-                    Err(e) => {
-                        log::error!("failed to convert {c} to u8: {e}");
-                        None
-                    }
-                })
-                .collect()
-        })
-        .collect();
-
-    let mut map = Map {
-        elevations,
-        start_position: (0, 0),
-        end_position: (0, 0),
-    };
+use std::{cmp::Ordering, fmt};
 
-    let e = u8::try_from('E')?;
-    let s = u8::try_from('S')?;
+use anyhow::{anyhow, bail};
 
-    for (x, row) in map.elevations.iter().enumerate() {
-        for (y, col) in row.iter().enumerate() {
-            if *col == e || *col == s {
-                map.end_position = (x, y);
-            }
-        }
-    }
+use crate::{
+    geometry::Point,
+    grid::{Grid, Position},
+    pathfinding::{self, Graph, Search},
+    visualize::{self, Color, Frame, Visualize},
+    Answers,
+};
 
-    let mut paths: Vec<u32> = vec![];
-    let mut previous_positions = HashSet::new();
+pub fn main(input: String) -> anyhow::Result<Answers> {
+    let map = parse(&input)?;
+    visualize::show(&map);
 
-    climb(
-        map.start_position,
-        &mut previous_positions,
+    // Part 1
+    // Every step covers one square at most, so the distance as the crow walks never overestimates
+    let end = point(map.end_position);
+    let search = pathfinding::astar(
         &map,
-        0,
-        &mut paths,
+        [map.start_position],
+        |&square| square == map.end_position,
+        |&square| point(square).manhattan(end),
     );
+    show_search(&map, 1, &search);
 
-    paths.sort_unstable();
-    paths.reverse();
+    let Some(shortest_path) = search.goal_cost() else {
+        bail!("there is no way from S up to E");
+    };
+    log::info!("part 1, shortest path from S to E is {shortest_path} steps");
 
+    // Part 2
+    let lowest = map
+        .elevations
+        .positions()
+        .filter(|&square| map.elevation(square) == b'a');
+    let search = pathfinding::bfs(&map, lowest, |&square| square == map.end_position);
+    show_search(&map, 2, &search);
+
+    let Some(shortest_hike) = search.goal_cost() else {
+        bail!("there is no way from any square at elevation a up to E");
+    };
     log::info!(
-        "part 1, shortest path from S to E is {} steps",
-        paths.first().unwrap_or(&0)
+        "part 2, shortest path from any square at elevation a to E is {shortest_hike} steps"
     );
 
-    Ok(())
+    Ok(Answers::new(shortest_path, shortest_hike))
+}
+
+/// Parses the heightmap.
+pub(crate) fn parse(input: &str) -> anyhow::Result<Map> {
+    let elevations = Grid::parse(input, |c| {
+        u8::try_from(c).map_err(|e| anyhow!("failed to convert {c} to u8: {e}"))
+    })?;
+
+    let find = |mark: u8| {
+        elevations
+            .iter()
+            .find(|&(_, &square)| square == mark)
+            .map(|(position, _)| position)
+    };
+    let start_position = find(b'S').ok_or_else(|| anyhow!("the map has no start S"))?;
+    let end_position = find(b'E').ok_or_else(|| anyhow!("the map has no end E"))?;
+
+    Ok(Map {
+        start_position,
+        end_position,
+        elevations,
+    })
+}
+
+/// The point on a plane at `position` on a grid.
+fn point((row, col): Position) -> Point {
+    Point::new(col as i64, row as i64)
 }
 
 /// The map of the terrain.
 #[derive(Debug)]
-struct Map {
+pub(crate) struct Map {
     /// The start position S.
-    start_position: (usize, usize),
+    start_position: Position,
     /// The end position E.
-    end_position: (usize, usize),
+    end_position: Position,
     /// The elevations of each square on the map.
-    elevations: Vec<Vec<u8>>,
+    elevations: Grid<u8>,
+}
+
+impl Map {
+    /// The elevation of `square`, from `a` to `z`. The start S is at `a` and the end E at `z`.
+    fn elevation(&self, square: Position) -> u8 {
+        match self.elevations[square] {
+            b'S' => b'a',
+            b'E' => b'z',
+            elevation => elevation,
+        }
+    }
+}
+
+impl Graph for Map {
+    type Node = Position;
+
+    /// The squares next to `square` that are at most one higher, each a step away.
+    fn neighbours(&self, &square: &Position) -> Vec<(Position, u64)> {
+        let highest = self.elevation(square).saturating_add(1);
+
+        self.elevations
+            .neighbours(square)
+            .filter(|&next| self.elevation(next) <= highest)
+            .map(|next| (next, 1))
+            .collect()
+    }
 }
 
 impl fmt::Display for Map {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        for row in &self.elevations {
+        for row in self.elevations.rows() {
             for col in row {
                 write!(f, "{}", char::from(*col))?;
             }
@@ -78,83 +124,138 @@ impl fmt::Display for Map {
     }
 }
 
-fn climb(
-    current_position: (usize, usize),
-    previous_positions: &mut HashSet<(usize, usize)>,
-    map: &Map,
-    step: u32,
-    paths: &mut Vec<u32>,
-) {
-    if step > 500 {
-        return;
-    }
+impl Visualize for Map {
+    fn frame(&self) -> Frame {
+        let mut frame = Frame::from_text("Heightmap", &self.to_string());
 
-    let current_elevation = map
-        .elevations
-        .get(current_position.0)
-        .unwrap()
-        .get(current_position.1)
-        .unwrap();
-
-    for square in adjacent_squares(current_position, previous_positions, map) {
-        previous_positions.clear();
-
-        // Add this solution to the vector of solutions
-        if square == map.end_position {
-            paths.push(step + 1);
-            continue;
-        }
-
-        let square_elevation = map.elevations.get(square.0).unwrap().get(square.1).unwrap();
-
-        // For squares that are not the starting position, this square is too high to be a valid
-        // next step
-        if current_position != map.start_position && *square_elevation > *current_elevation + 1 {
-            continue;
+        for (x, y) in self.elevations.positions() {
+            let height = u32::from(self.elevation((x, y)).saturating_sub(b'a'));
+            frame.paint(y, x, Color::shade(height, 25));
         }
 
-        climb(square, previous_positions, map, step + 1, paths);
+        frame
     }
 }
 
-fn adjacent_squares(
-    current_position: (usize, usize),
-    previous_positions: &HashSet<(usize, usize)>,
-    map: &Map,
-) -> Vec<(usize, usize)> {
-    let mut adjacent_squares = Vec::with_capacity(4);
-
-    // Up
-    if current_position.0 > 0 {
-        let up = (current_position.0 - 1, current_position.1);
-        if !previous_positions.contains(&up) {
-            adjacent_squares.push(up);
-        }
+/// Shows how `search` spread out over the map a step at a time, then the path it found.
+fn show_search(map: &Map, part: u8, search: &Search<Position>) {
+    if !visualize::enabled() {
+        return;
     }
 
-    // Down
-    if current_position.0 < map.elevations.len() - 1 {
-        let down = (current_position.0 + 1, current_position.1);
-        if !previous_positions.contains(&down) {
-            adjacent_squares.push(down);
-        }
+    let furthest = search.costs().values().copied().max().unwrap_or_default();
+    for steps in 0..=furthest {
+        visualize::show(&Frontier {
+            map,
+            part,
+            search,
+            steps,
+            path: None,
+        });
     }
 
-    // Left
-    if current_position.1 > 0 {
-        let left = (current_position.0, current_position.1 - 1);
-        if !previous_positions.contains(&left) {
-            adjacent_squares.push(left);
-        }
+    if let Some(path) = search.goal_path() {
+        visualize::show(&Frontier {
+            map,
+            part,
+            search,
+            steps: furthest,
+            path: Some(path),
+        });
     }
+}
+
+/// The squares a search has reached within a number of steps.
+struct Frontier<'a> {
+    map: &'a Map,
+    part: u8,
+    search: &'a Search<Position>,
+    steps: u64,
+    /// The path the search found, once it is done.
+    path: Option<Vec<Position>>,
+}
+
+impl Visualize for Frontier<'_> {
+    fn frame(&self) -> Frame {
+        let mut frame = self.map.frame();
+        frame.title = format!("Part {}, {} steps", self.part, self.steps);
 
-    // Right
-    if current_position.1 < map.elevations[0].len() - 1 {
-        let right = (current_position.0, current_position.1 + 1);
-        if !previous_positions.contains(&right) {
-            adjacent_squares.push(right);
+        for (&(x, y), &steps) in self.search.costs() {
+            match steps.cmp(&self.steps) {
+                Ordering::Less => frame.paint(y, x, Color::Blue),
+                Ordering::Equal => frame.paint(y, x, Color::Red),
+                Ordering::Greater => {}
+            }
+        }
+        for &(x, y) in self.path.iter().flatten() {
+            frame.paint(y, x, Color::Yellow);
         }
+
+        frame
     }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::{HashMap, VecDeque};
+
+    use super::*;
+    use crate::gen;
+
+    /// Searches breadth first uphill from S until it reaches E, then downhill from E until it
+    /// reaches a square at elevation a.
+    fn reference(input: &str) -> Answers {
+        let map: Vec<&[u8]> = input.lines().map(str::as_bytes).collect();
+        let elevation = |b: u8| match b {
+            b'S' => b'a',
+            b'E' => b'z',
+            b => b,
+        };
+
+        let search = |from: u8, is_goal: &dyn Fn(u8) -> bool, can_step: &dyn Fn(u8, u8) -> bool| {
+            let start = map
+                .iter()
+                .enumerate()
+                .find_map(|(r, row)| row.iter().position(|&b| b == from).map(|c| (r, c)))
+                .unwrap();
+            let mut steps = HashMap::from([(start, 0)]);
+            let mut queue = VecDeque::from([start]);
+
+            while let Some((r, c)) = queue.pop_front() {
+                if is_goal(map[r][c]) {
+                    return steps[&(r, c)];
+                }
+
+                let neighbours = [
+                    (r.wrapping_sub(1), c),
+                    (r + 1, c),
+                    (r, c.wrapping_sub(1)),
+                    (r, c + 1),
+                ];
+                for next in neighbours {
+                    let Some(&square) = map.get(next.0).and_then(|row| row.get(next.1)) else {
+                        continue;
+                    };
+                    if can_step(elevation(map[r][c]), elevation(square))
+                        && !steps.contains_key(&next)
+                    {
+                        steps.insert(next, steps[&(r, c)] + 1);
+                        queue.push_back(next);
+                    }
+                }
+            }
+
+            panic!("no path from {}", char::from(from));
+        };
+
+        let uphill = search(b'S', &|b| b == b'E', &|from, to| to <= from + 1);
+        let downhill = search(b'E', &|b| elevation(b) == b'a', &|from, to| from <= to + 1);
 
-    adjacent_squares
+        Answers::new(uphill, downhill)
+    }
+
+    #[test]
+    fn matches_reference() {
+        gen::assert_matches_reference(12, main, reference);
+    }
 }
@@ -1,20 +1,32 @@
-pub fn main(input: String) -> anyhow::Result<()> {
-    let rock_paper_scissors: Vec<(char, char)> = input
-        .lines()
-        .map(|s| {
-            s.split_once(' ')
-                .map(|(s1, s2)| (s1.parse().unwrap(), s2.parse().unwrap()))
-                .unwrap()
-        })
-        .collect();
+use anyhow::anyhow;
+
+use crate::Answers;
+
+pub fn main(input: String) -> anyhow::Result<Answers> {
+    let rock_paper_scissors = parse(&input)?;
 
     // Part 1
-    log::info!("part 1 total score: {}", part1(&rock_paper_scissors));
+    let part1 = part1(&rock_paper_scissors);
+    log::info!("part 1 total score: {part1}");
 
     // Part 2
-    log::info!("part 2 total score: {}", part2(&rock_paper_scissors));
+    let part2 = part2(&rock_paper_scissors);
+    log::info!("part 2 total score: {part2}");
+
+    Ok(Answers::new(part1, part2))
+}
 
-    Ok(())
+/// Parses the strategy guide into pairs of columns.
+pub(crate) fn parse(input: &str) -> anyhow::Result<Vec<(char, char)>> {
+    input
+        .lines()
+        .map(|s| {
+            s.split_once(' ')
+                .and_then(|(s1, s2)| s1.parse().ok().zip(s2.parse().ok()))
+                .filter(|&(s1, s2)| matches!(s1, 'A'..='C') && matches!(s2, 'X'..='Z'))
+                .ok_or_else(|| anyhow!("failed to parse game {s}"))
+        })
+        .collect()
 }
 
 /// Part 1 solution.
@@ -48,3 +60,37 @@ fn part2(games: &[(char, char)]) -> u64 {
         })
         .sum()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::gen;
+
+    /// Scores each round with modular arithmetic instead of lookup tables.
+    fn reference(input: &str) -> Answers {
+        let rounds: Vec<(u64, u64)> = input
+            .lines()
+            .map(|line| {
+                let bytes = line.as_bytes();
+                (u64::from(bytes[0] - b'A'), u64::from(bytes[2] - b'X'))
+            })
+            .collect();
+
+        // Shapes are 0 to 2 and each beats the one before it
+        let part1: u64 = rounds
+            .iter()
+            .map(|&(opponent, you)| you + 1 + 3 * ((you + 4 - opponent) % 3))
+            .sum();
+        let part2: u64 = rounds
+            .iter()
+            .map(|&(opponent, outcome)| (opponent + outcome + 2) % 3 + 1 + 3 * outcome)
+            .sum();
+
+        Answers::new(part1, part2)
+    }
+
+    #[test]
+    fn matches_reference() {
+        gen::assert_matches_reference(2, main, reference);
+    }
+}
@@ -1,11 +1,40 @@
-pub fn main(input: String) -> anyhow::Result<()> {
-    let common_item_priority_sum: u64 = input
-        .lines()
+use anyhow::bail;
+
+use crate::{letters::LetterSet, runner::Variant, Answers};
+
+/// The ways of finding shared items, the first being the default.
+pub const VARIANTS: &[Variant] = &[
+    Variant {
+        name: "letterset",
+        solution: main,
+        stream: None,
+    },
+    Variant {
+        name: "contains",
+        solution: main_contains,
+        stream: None,
+    },
+];
+
+pub fn main(input: String) -> anyhow::Result<Answers> {
+    solve(&input, shared_item)
+}
+
+/// Finds shared items by searching the other lists for each item of the first, instead of
+/// intersecting sets of them.
+fn main_contains(input: String) -> anyhow::Result<Answers> {
+    solve(&input, shared_item_contains)
+}
+
+/// Finds both priority sums with `shared`, which works like [`shared_item`].
+fn solve(input: &str, shared: fn(&[&str]) -> Option<Item>) -> anyhow::Result<Answers> {
+    let rucksacks = parse(input)?;
+
+    let common_item_priority_sum: u64 = rucksacks
+        .iter()
         .map(|rucksack| {
             let (first_compartment, second_compartment) = rucksack.split_at(rucksack.len() / 2);
-            first_compartment
-                .chars()
-                .find(|&c| second_compartment.contains(c))
+            shared(&[first_compartment, second_compartment])
                 .map(get_priority)
                 .unwrap_or_default()
         })
@@ -14,33 +43,49 @@ pub fn main(input: String) -> anyhow::Result<()> {
     // Part 1
     log::info!("common item priority sum: {common_item_priority_sum}");
 
-    let badge_priority_sum: u64 = input
-        .lines()
-        .collect::<Vec<_>>()
+    let badge_priority_sum: u64 = rucksacks
         .chunks(3)
-        .map(|group| {
-            group
-                .first()
-                .and_then(|first_rucksack| {
-                    first_rucksack
-                        .chars()
-                        .find(|&c| {
-                            matches!(
-                                (group.get(1), group.get(2)),
-                                (Some(second_rucksack), Some(third_rucksack))
-                                    if second_rucksack.contains(c) && third_rucksack.contains(c)
-                            )
-                        })
-                        .map(get_priority)
-                })
-                .unwrap_or_default()
-        })
+        .filter(|group| group.len() == 3)
+        .map(|group| shared(group).map(get_priority).unwrap_or_default())
         .sum();
 
     // Part 2
     log::info!("badge priority sum: {badge_priority_sum}");
 
-    Ok(())
+    Ok(Answers::new(common_item_priority_sum, badge_priority_sum))
+}
+
+/// Finds an item type that is in every one of `lists` by intersecting the sets of their items.
+/// Items must be letters.
+fn shared_item(lists: &[&str]) -> Option<Item> {
+    let mut sets = lists.iter().map(|list| list.parse::<LetterSet>().ok());
+    let first = sets.next()??;
+
+    sets.try_fold(first, |shared, set| Some(shared.intersection(&set?)))?
+        .iter()
+        .next()
+}
+
+/// Works like [`shared_item`], but checks each item of the first list against the other lists
+/// one at a time.
+fn shared_item_contains(lists: &[&str]) -> Option<Item> {
+    let (first, rest) = lists.split_first()?;
+    first
+        .chars()
+        .find(|&c| rest.iter().all(|list| list.contains(c)))
+}
+
+/// Parses the rucksacks, one per line.
+pub(crate) fn parse(input: &str) -> anyhow::Result<Vec<&str>> {
+    if let Some(item) = input
+        .lines()
+        .flat_map(str::chars)
+        .find(|c| !c.is_ascii_alphabetic())
+    {
+        bail!("{item:?} is not a valid item");
+    }
+
+    Ok(input.lines().collect())
 }
 
 /// Type alias for the rucksack items.
@@ -54,3 +99,56 @@ fn get_priority(item: Item) -> u64 {
         item as u64 - 'A' as u64 + 27
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use super::*;
+    use crate::gen;
+
+    /// Intersects sets of items.
+    fn reference(input: &str) -> Answers {
+        let priority = |item: &char| {
+            ('a'..='z')
+                .chain('A'..='Z')
+                .position(|c| c == *item)
+                .unwrap() as u64
+                + 1
+        };
+        let items = |s: &str| s.chars().collect::<HashSet<_>>();
+
+        let part1: u64 = input
+            .lines()
+            .map(|line| {
+                let (first, second) = line.split_at(line.len() / 2);
+                items(first)
+                    .intersection(&items(second))
+                    .map(priority)
+                    .sum::<u64>()
+            })
+            .sum();
+
+        let lines: Vec<_> = input.lines().collect();
+        let part2: u64 = lines
+            .chunks(3)
+            .map(|group| {
+                let badges: HashSet<_> = items(group[0])
+                    .intersection(&items(group[1]))
+                    .copied()
+                    .collect();
+                badges
+                    .intersection(&items(group[2]))
+                    .map(priority)
+                    .sum::<u64>()
+            })
+            .sum();
+
+        Answers::new(part1, part2)
+    }
+
+    #[test]
+    fn matches_reference() {
+        gen::assert_matches_reference(3, main, reference);
+    }
+}
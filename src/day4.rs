@@ -1,43 +1,104 @@
-pub fn main(input: String) -> anyhow::Result<()> {
-    let pairs: Vec<((u32, u32), (u32, u32))> = input
-        .lines()
-        .map(|line| {
-            line.split_once(',')
-                .and_then(|(first_range, second_range)| {
-                    first_range
-                        .split_once('-')
-                        .and_then(|(start, end)| start.parse().ok().zip(end.parse().ok()))
-                        .zip(
-                            second_range
-                                .split_once('-')
-                                .and_then(|(start, end)| start.parse().ok().zip(end.parse().ok())),
-                        )
-                })
-                .unwrap_or_default()
-        })
-        .collect();
+use anyhow::anyhow;
+
+use crate::{
+    interval::{Interval, IntervalSet},
+    parsing::integers,
+    Answers,
+};
+
+pub fn main(input: String) -> anyhow::Result<Answers> {
+    let pairs = parse(&input)?;
 
     // Part 1
-    let fully_contained_count = pairs.iter().filter(one_range_is_fully_contained).count();
+    let fully_contained_count = pairs
+        .iter()
+        .filter(|(first, second)| first.covers(second) || second.covers(first))
+        .count();
     log::info!("full contained count: {fully_contained_count}");
 
     // Part 2
-    let any_overlap_count = pairs.iter().filter(ranges_have_any_overlap).count();
+    let any_overlap_count = pairs
+        .iter()
+        .filter(|(first, second)| first.overlaps(second))
+        .count();
     log::info!("any overlap count: {any_overlap_count}");
 
-    Ok(())
+    // Sections are numbered from one, up to the last anyone is assigned
+    let covered: IntervalSet = pairs
+        .iter()
+        .flat_map(|&(first, second)| [first, second])
+        .collect();
+    if let Some(span) = covered.span() {
+        let sections = IntervalSet::from(Interval::new(1, span.end()).unwrap_or(span));
+        let uncovered = sections.difference(&covered);
+        if uncovered.is_empty() {
+            log::info!("every section is covered");
+        } else {
+            log::info!(
+                "sections nobody covers: {uncovered} ({} in all)",
+                uncovered.len()
+            );
+        }
+    }
+
+    Ok(Answers::new(fully_contained_count, any_overlap_count))
 }
 
-/// Is one range fully contained within the other?
-fn one_range_is_fully_contained(ranges: &&((u32, u32), (u32, u32))) -> bool {
-    let lhs = ranges.0;
-    let rhs = ranges.1;
-    (lhs.0 <= rhs.0 && lhs.1 >= rhs.1) || (rhs.0 <= lhs.0 && rhs.1 >= lhs.1)
+/// Parses the pairs of section assignments, one pair per line, like `2-4,6-8`.
+pub(crate) fn parse(input: &str) -> anyhow::Result<Vec<(Interval, Interval)>> {
+    input
+        .lines()
+        .map(|line| {
+            let assignment = |start, end| {
+                Interval::new(start, end)
+                    .ok_or_else(|| anyhow!("assignment {start}-{end} in `{line}` runs backwards"))
+            };
+
+            match integers(line)?[..] {
+                [first_start, first_end, second_start, second_end] => Ok((
+                    assignment(first_start, first_end)?,
+                    assignment(second_start, second_end)?,
+                )),
+                _ => Err(anyhow!("expected a pair of assignments, got `{line}`")),
+            }
+        })
+        .collect()
 }
 
-/// Do the ranges overlap at all?
-fn ranges_have_any_overlap(ranges: &&((u32, u32), (u32, u32))) -> bool {
-    let lhs = ranges.0;
-    let rhs = ranges.1;
-    (rhs.0 <= lhs.0 || lhs.1 >= rhs.0) && lhs.0 <= rhs.1
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use super::*;
+    use crate::gen;
+
+    /// Compares sets of sections.
+    fn reference(input: &str) -> Answers {
+        let pairs: Vec<(HashSet<u32>, HashSet<u32>)> = input
+            .lines()
+            .map(|line| {
+                let n: Vec<u32> = line
+                    .split(|c: char| !c.is_ascii_digit())
+                    .map(|n| n.parse().unwrap())
+                    .collect();
+                ((n[0]..=n[1]).collect(), (n[2]..=n[3]).collect())
+            })
+            .collect();
+
+        let contained = pairs
+            .iter()
+            .filter(|(first, second)| first.is_subset(second) || second.is_subset(first))
+            .count();
+        let overlapping = pairs
+            .iter()
+            .filter(|(first, second)| !first.is_disjoint(second))
+            .count();
+
+        Answers::new(contained, overlapping)
+    }
+
+    #[test]
+    fn matches_reference() {
+        gen::assert_matches_reference(4, main, reference);
+    }
 }
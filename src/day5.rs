@@ -1,5 +1,19 @@
-pub fn main(input: String) -> anyhow::Result<()> {
-    let (raw_initial_stack, raw_instructions) = input.split_once("\n\n").unwrap();
+use std::fmt;
+
+use anyhow::{anyhow, bail};
+
+use crate::{
+    explain::narrate,
+    parsing::{paragraphs, ParseError, Template},
+    visualize::{self, Color, Frame, Visualize},
+    Answers,
+};
+
+pub fn main(input: String) -> anyhow::Result<Answers> {
+    let mut blocks = paragraphs(&input);
+    let (Some(raw_initial_stack), Some(raw_instructions)) = (blocks.next(), blocks.next()) else {
+        bail!("failed to find the end of the initial stacks");
+    };
 
     // There are nine stacks in this particular case
     let mut stacks = Stacks::<9>::default();
@@ -7,7 +21,8 @@ pub fn main(input: String) -> anyhow::Result<()> {
     let raw_initial_stack_rows: Vec<_> = raw_initial_stack
         .split('\n')
         .filter_map(|row| {
-            let row = row.trim();
+            // Leading spaces line crates up with their stacks, so only trim the end
+            let row = row.trim_end();
             // Skip the row with the stack numbers
             (!row.is_empty() && !row.contains('1')).then_some(row)
         })
@@ -21,55 +36,67 @@ pub fn main(input: String) -> anyhow::Result<()> {
         for (i, _crate) in raw_initial_stack_row.chars().skip(1).step_by(4).enumerate() {
             // Should always be true with valid input, but check just in case
             if _crate.is_ascii_uppercase() {
-                stacks[i].push(_crate);
+                stack(&mut stacks, i + 1)?.push(_crate);
             }
         }
     }
 
-    let instructions: Vec<_> = raw_instructions
-        .split('\n')
-        .filter_map(|s| {
-            let s = s.trim();
-            if s.is_empty() {
-                None
-            } else {
-                Instruction::try_from(s).ok()
-            }
-        })
-        .collect();
+    narrate!("Starting stacks:\n{}", draw(&stacks));
+
+    let instructions = raw_instructions
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .map(Instruction::try_from)
+        .collect::<Result<Vec<_>, _>>()?;
 
     // Part 1
     // We need a copy of the stacks for each part because we are mutating
     let mut stacks_for_part1 = stacks.clone();
+    narrate!("Part 1, the crane moves crates one at a time.");
+    visualize::show(&Crane::new(1, &stacks_for_part1, None));
 
     for instruction in &instructions {
+        // Stacks are the same height in both parts, so checking here covers part 2 as well
+        let available = stack(&mut stacks_for_part1, instruction.start)?.len();
+        if instruction.quantity > available {
+            bail!(
+                "cannot move {} crates from stack {} holding {available}",
+                instruction.quantity,
+                instruction.start
+            );
+        }
+
         for _ in 0..instruction.quantity {
-            let moved_crate = stacks_for_part1[instruction.start - 1]
+            let moved_crate = stack(&mut stacks_for_part1, instruction.start)?
                 .pop()
                 .unwrap_or_default();
 
-            stacks_for_part1[instruction.end - 1].push(moved_crate);
+            stack(&mut stacks_for_part1, instruction.end)?.push(moved_crate);
         }
+
+        narrate!("{instruction}\n{}", draw(&stacks_for_part1));
+        visualize::show(&Crane::new(1, &stacks_for_part1, Some(instruction)));
     }
 
-    let top_crates_for_part1 = stacks_for_part1
+    let top_crates_for_part1: String = stacks_for_part1
         .iter()
-        .fold(String::new(), |mut top, stack| {
-            top.push(*stack.last().unwrap_or(&char::default()));
-            top
-        });
+        .filter_map(|stack| stack.last())
+        .collect();
 
-    println!("top crates for part 1: {top_crates_for_part1}");
+    log::info!("top crates for part 1: {top_crates_for_part1}");
 
     // Part 2
     let mut stacks_for_part2 = stacks;
+    narrate!("Part 2, the crane moves all the crates of a move at once.");
+    visualize::show(&Crane::new(2, &stacks_for_part2, None));
 
     for instruction in &instructions {
         // Need a temporary holding place for moved crates in each instruction
         let mut temp = Vec::with_capacity(instruction.quantity);
 
         for _ in 0..instruction.quantity {
-            let moved_crate = stacks_for_part2[instruction.start - 1]
+            let moved_crate = stack(&mut stacks_for_part2, instruction.start)?
                 .pop()
                 .unwrap_or_default();
 
@@ -80,20 +107,21 @@ pub fn main(input: String) -> anyhow::Result<()> {
         temp.reverse();
 
         for moved_crate in temp {
-            stacks_for_part2[instruction.end - 1].push(moved_crate);
+            stack(&mut stacks_for_part2, instruction.end)?.push(moved_crate);
         }
+
+        narrate!("{instruction}\n{}", draw(&stacks_for_part2));
+        visualize::show(&Crane::new(2, &stacks_for_part2, Some(instruction)));
     }
 
-    let top_crates_for_part2 = stacks_for_part2
+    let top_crates_for_part2: String = stacks_for_part2
         .iter()
-        .fold(String::new(), |mut top, stack| {
-            top.push(*stack.last().unwrap_or(&char::default()));
-            top
-        });
+        .filter_map(|stack| stack.last())
+        .collect();
 
-    println!("top crates for part 2: {top_crates_for_part2}");
+    log::info!("top crates for part 2: {top_crates_for_part2}");
 
-    Ok(())
+    Ok(Answers::new(top_crates_for_part1, top_crates_for_part2))
 }
 
 /// A crate contains an ASCII uppercase letter.
@@ -102,41 +130,187 @@ type Crate = char;
 /// There are `N` stacks of `Crate`s.
 type Stacks<const N: usize> = [Vec<Crate>; N];
 
+/// Gets stack number `n`, counting from one as the instructions do.
+fn stack<const N: usize>(stacks: &mut Stacks<N>, n: usize) -> anyhow::Result<&mut Vec<Crate>> {
+    n.checked_sub(1)
+        .and_then(|i| stacks.get_mut(i))
+        .ok_or_else(|| anyhow!("stack {n} does not exist"))
+}
+
+/// Draws the stacks the way the puzzle does, with the numbers of the stacks underneath.
+fn draw<const N: usize>(stacks: &Stacks<N>) -> String {
+    let height = stacks.iter().map(Vec::len).max().unwrap_or_default();
+    let mut drawing = String::new();
+
+    for level in (0..height).rev() {
+        let row: Vec<String> = stacks
+            .iter()
+            .map(|stack| stack.get(level).map_or("   ".into(), |c| format!("[{c}]")))
+            .collect();
+        drawing.push_str(row.join(" ").trim_end());
+        drawing.push('\n');
+    }
+
+    let numbers: Vec<String> = (1..=N).map(|n| format!(" {n} ")).collect();
+    drawing.push_str(numbers.join(" ").trim_end());
+    drawing.push('\n');
+
+    drawing
+}
+
+/// The stacks as the crane leaves them after a move.
+struct Crane<'a, const N: usize> {
+    part: u8,
+    stacks: &'a Stacks<N>,
+    /// The move just made, if any.
+    last_move: Option<&'a Instruction>,
+}
+
+impl<'a, const N: usize> Crane<'a, N> {
+    fn new(part: u8, stacks: &'a Stacks<N>, last_move: Option<&'a Instruction>) -> Self {
+        Crane {
+            part,
+            stacks,
+            last_move,
+        }
+    }
+}
+
+impl<const N: usize> Visualize for Crane<'_, N> {
+    fn frame(&self) -> Frame {
+        let title = match self.last_move {
+            Some(instruction) => format!("Part {}, {instruction}", self.part),
+            None => format!("Part {}, starting stacks", self.part),
+        };
+
+        let mut frame = Frame::from_text(title, &draw(self.stacks));
+        let height = frame.height();
+
+        // Highlight the crates that just moved, at the top of the stack they moved to
+        if let Some(instruction) = self.last_move {
+            let Some(stack) = instruction
+                .end
+                .checked_sub(1)
+                .and_then(|i| self.stacks.get(i))
+            else {
+                return frame;
+            };
+            let x = (instruction.end - 1) * 4;
+            for level in stack.len().saturating_sub(instruction.quantity)..stack.len() {
+                // The numbers of the stacks take up the bottom row
+                let y = height - 2 - level;
+                for dx in 0..3 {
+                    frame.paint(x + dx, y, Color::Yellow);
+                }
+            }
+        }
+
+        frame
+    }
+}
+
 /// An instruction moves `quantity` crates from `start` stack to `end` stack.
-struct Instruction {
+pub(crate) struct Instruction {
     quantity: usize,
     start: usize,
     end: usize,
 }
 
+impl fmt::Display for Instruction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "move {} from {} to {}",
+            self.quantity, self.start, self.end
+        )
+    }
+}
+
 impl TryFrom<&str> for Instruction {
-    type Error = &'static str;
+    type Error = ParseError;
 
     fn try_from(value: &str) -> Result<Self, Self::Error> {
-        let parts: Vec<_> = value
-            .split_whitespace()
-            .filter_map(|part| {
-                let part = part.trim();
-                (!part.is_empty()).then_some(part)
-            })
-            .collect();
+        const MOVE: Template = Template::new("move {quantity} from {start} to {end}");
+
+        let fields = MOVE.captures(value.trim())?;
 
         Ok(Instruction {
-            quantity: parts
-                .get(1)
-                .ok_or("failed to find quantity")?
-                .parse()
-                .map_err(|_| "failed to parse quantity")?,
-            start: parts
-                .get(3)
-                .ok_or("failed to find start")?
-                .parse()
-                .map_err(|_| "failed to parse start")?,
-            end: parts
-                .get(5)
-                .ok_or("failed to find end")?
-                .parse()
-                .map_err(|_| "failed to parse end")?,
+            quantity: fields.parse("quantity")?,
+            start: fields.parse("start")?,
+            end: fields.parse("end")?,
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{explain, gen};
+
+    /// Reads the stacks column by column and moves crates one step at a time.
+    fn reference(input: &str) -> Answers {
+        let (drawing, moves) = input.split_once("\n\n").unwrap();
+        let rows: Vec<&[u8]> = drawing.lines().map(str::as_bytes).collect();
+        let (numbers, rows) = rows.split_last().unwrap();
+
+        let stacks: Vec<Vec<u8>> = (0..numbers.len().div_ceil(4))
+            .map(|i| {
+                rows.iter()
+                    .rev()
+                    .filter_map(|row| row.get(4 * i + 1).copied())
+                    .filter(u8::is_ascii_uppercase)
+                    .collect()
+            })
+            .collect();
+
+        let (mut one_at_a_time, mut all_at_once) = (stacks.clone(), stacks);
+
+        for line in moves.lines() {
+            let n: Vec<usize> = line
+                .split(' ')
+                .filter_map(|word| word.parse().ok())
+                .collect();
+            let (quantity, from, to) = (n[0], n[1] - 1, n[2] - 1);
+
+            for _ in 0..quantity {
+                let moved = one_at_a_time[from].pop().unwrap();
+                one_at_a_time[to].push(moved);
+            }
+
+            let at = all_at_once[from].len() - quantity;
+            let moved = all_at_once[from].split_off(at);
+            all_at_once[to].extend(moved);
+        }
+
+        let tops = |stacks: &[Vec<u8>]| -> String {
+            stacks
+                .iter()
+                .filter_map(|stack| stack.last().map(|&c| char::from(c)))
+                .collect()
+        };
+
+        Answers::new(tops(&one_at_a_time), tops(&all_at_once))
+    }
+
+    #[test]
+    fn matches_reference() {
+        gen::assert_matches_reference(5, main, reference);
+    }
+
+    #[test]
+    fn explain_moves() {
+        let example = include_str!("../tests/examples/day5.txt");
+        let (_, narrative) = explain::capture(|| main(example.to_string()));
+
+        let numbers = " 1   2   3   4   5   6   7   8   9\n";
+        assert!(narrative.starts_with(&format!(
+            "Starting stacks:\n    [D]\n[N] [C]\n[Z] [M] [P]\n{numbers}\n"
+        )));
+        assert!(narrative.contains(&format!(
+            "move 3 from 1 to 3\n        [Z]\n        [N]\n    [C] [D]\n    [M] [P]\n{numbers}"
+        )));
+        assert!(narrative.contains(&format!(
+            "move 3 from 1 to 3\n        [D]\n        [N]\n    [C] [Z]\n    [M] [P]\n{numbers}"
+        )));
+    }
+}
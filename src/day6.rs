@@ -1,34 +1,186 @@
-use std::collections::HashSet;
+use std::{
+    collections::{HashSet, VecDeque},
+    io::BufRead,
+};
 
-pub fn main(input: String) -> anyhow::Result<()> {
-    let chars: Vec<_> = input.chars().collect();
+use anyhow::bail;
 
+use crate::{letters::LetterSet, runner::Variant, Answers};
+
+/// The ways of finding markers, the first being the default.
+pub const VARIANTS: &[Variant] = &[
+    Variant {
+        name: "letterset",
+        solution: main,
+        stream: Some(main_stream),
+    },
+    Variant {
+        name: "hashset",
+        solution: main_hashset,
+        stream: None,
+    },
+    Variant {
+        name: "bitmask",
+        solution: main_bitmask,
+        stream: None,
+    },
+];
+
+pub fn main(input: String) -> anyhow::Result<Answers> {
+    Ok(solve(&parse(&input)?, first_distinct_chunk))
+}
+
+/// The characters of the datastream, without the whitespace it ends with. Fails on anything else
+/// that is not a letter.
+fn parse(input: &str) -> anyhow::Result<Vec<char>> {
+    let chars: Vec<_> = input.trim_end().chars().collect();
+    if let Some(c) = chars.iter().find(|&&c| LetterSet::index(c).is_none()) {
+        bail!("{c:?} is not a letter");
+    }
+
+    Ok(chars)
+}
+
+/// Finds markers like [`main`], but reads the datastream a buffer at a time, keeping only the
+/// letters of the current run. The rest of the datastream is still read once both markers are
+/// found, so that it fails on the same datastreams as `main`.
+pub fn main_stream(input: &mut dyn BufRead) -> anyhow::Result<Answers> {
+    let mut run = DistinctRun::new();
+    let (mut start_of_packet, mut start_of_message) = (None, None);
+    // The whitespace the datastream ended with, if it has
+    let mut ended = None;
+
+    loop {
+        let buffer = input.fill_buf()?;
+        if buffer.is_empty() {
+            break;
+        }
+
+        for &byte in buffer {
+            if !byte.is_ascii() {
+                bail!("the datastream is not ASCII");
+            }
+
+            // Whitespace may only come at the end, where `main` trims it off
+            let c = char::from(byte);
+            if c.is_ascii_whitespace() {
+                ended.get_or_insert(c);
+                continue;
+            }
+            if let Some(whitespace) = ended {
+                bail!("{whitespace:?} is not a letter");
+            }
+            if !c.is_ascii_alphabetic() {
+                bail!("{c:?} is not a letter");
+            }
+
+            // A start-of-message marker is a start-of-packet marker too, so the packet is always
+            // found by then
+            if start_of_message.is_none() {
+                let len = run.push(c);
+                if len >= START_OF_PACKET {
+                    start_of_packet.get_or_insert(run.read);
+                }
+                if len >= START_OF_MESSAGE {
+                    start_of_message = Some(run.read);
+                }
+            }
+        }
+
+        let len = buffer.len();
+        input.consume(len);
+    }
+
+    Ok(answers(start_of_packet, start_of_message))
+}
+
+/// Finds markers with a set of the characters in each chunk, after reading them like [`main`].
+fn main_hashset(input: String) -> anyhow::Result<Answers> {
+    Ok(solve(&parse(&input)?, first_distinct_chunk_hashset))
+}
+
+/// Finds markers with a bitmask of the characters in each chunk, after reading them like [`main`].
+fn main_bitmask(input: String) -> anyhow::Result<Answers> {
+    Ok(solve(&parse(&input)?, first_distinct_chunk_bitmask))
+}
+
+/// The number of distinct characters in a start-of-packet marker.
+const START_OF_PACKET: usize = 4;
+
+/// The number of distinct characters in a start-of-message marker.
+const START_OF_MESSAGE: usize = 14;
+
+/// Finds both markers in `chars` with `find`, which works like [`first_distinct_chunk`].
+fn solve(chars: &[char], find: fn(&[char], usize) -> Option<usize>) -> Answers {
+    answers(find(chars, START_OF_PACKET), find(chars, START_OF_MESSAGE))
+}
+
+/// The answers for where the markers end, if they were found.
+fn answers(start_of_packet: Option<usize>, start_of_message: Option<usize>) -> Answers {
     // Part 1
-    log::info!(
-        "first start of packet marker: {}",
-        first_distinct_chunk(&chars, 4).unwrap_or_default()
-    );
+    let start_of_packet = start_of_packet.unwrap_or_default();
+    log::info!("first start of packet marker: {start_of_packet}");
 
     // Part 2
-    log::info!(
-        "first start of message marker: {}",
-        first_distinct_chunk(&chars, 14).unwrap_or_default()
-    );
+    let start_of_message = start_of_message.unwrap_or_default();
+    log::info!("first start of message marker: {start_of_message}");
 
-    Ok(())
+    Answers::new(start_of_packet, start_of_message)
 }
 
 /// Returns the number of characters processed in `chars` to arrive at a chunk of length `n` of all
 /// distinct characters. Returns `None` if no chunks of size `n` contain all distinct characters.
-///
-/// # Panics
-///
-/// Panics if `n` is zero.
+/// An empty chunk is trivially distinct, so it is found before processing any characters.
+/// Characters must be ASCII letters.
 fn first_distinct_chunk(chars: &[char], n: usize) -> Option<usize> {
     if n == 0 {
-        panic!("n must be greater than zero");
+        return Some(0);
     }
 
+    let mut run = DistinctRun::new();
+    chars.iter().position(|&c| run.push(c) >= n).map(|i| i + 1)
+}
+
+/// The letters read most recently with no letter twice, which slides along a datastream one letter
+/// at a time. A letter that is already in the run drops the run's letters up to and including the
+/// earlier one, so every letter is added and dropped at most once.
+#[derive(Debug, Clone)]
+struct DistinctRun {
+    /// The letters in the run.
+    letters: LetterSet,
+    /// The same letters in the order they were read, which is never more than every letter.
+    order: VecDeque<char>,
+    /// How many letters have been read.
+    read: usize,
+}
+
+impl DistinctRun {
+    fn new() -> DistinctRun {
+        DistinctRun {
+            letters: LetterSet::new(),
+            order: VecDeque::with_capacity(52),
+            read: 0,
+        }
+    }
+
+    /// Reads `letter`, returning how long the run is now. Panics if it is not an ASCII letter.
+    fn push(&mut self, letter: char) -> usize {
+        while self.letters.contains(letter) {
+            if let Some(dropped) = self.order.pop_front() {
+                self.letters.remove(dropped);
+            }
+        }
+
+        self.letters.insert(letter);
+        self.order.push_back(letter);
+        self.read += 1;
+        self.order.len()
+    }
+}
+
+/// Works like [`first_distinct_chunk`], but keeps the characters of each chunk in a set, so they
+/// can be anything.
+fn first_distinct_chunk_hashset(chars: &[char], n: usize) -> Option<usize> {
     let mut i = 0;
     let mut contains = HashSet::with_capacity(n);
 
@@ -47,3 +199,48 @@ fn first_distinct_chunk(chars: &[char], n: usize) -> Option<usize> {
 
     None
 }
+
+/// Works like [`first_distinct_chunk`], but keeps the characters of each chunk in a bitmask with
+/// one bit per ASCII character, so they can be any ASCII character.
+fn first_distinct_chunk_bitmask(chars: &[char], n: usize) -> Option<usize> {
+    // `windows` panics on empty chunks, which are trivially distinct anyway
+    if n == 0 {
+        return Some(0);
+    }
+
+    chars
+        .windows(n)
+        .position(|chunk| {
+            let seen = chunk
+                .iter()
+                .fold(0u128, |seen, &c| seen | 1 << (c as u32 % 128));
+            seen.count_ones() as usize == n
+        })
+        .map(|i| i + n)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::gen;
+
+    /// Compares every pair of characters in every window.
+    fn reference(input: &str) -> Answers {
+        let chars: Vec<char> = input.chars().collect();
+        let marker = |n: usize| {
+            (n..=chars.len())
+                .find(|&end| {
+                    let window = &chars[end - n..end];
+                    (0..n).all(|i| (i + 1..n).all(|j| window[i] != window[j]))
+                })
+                .unwrap_or_default()
+        };
+
+        Answers::new(marker(4), marker(14))
+    }
+
+    #[test]
+    fn matches_reference() {
+        gen::assert_matches_reference(6, main, reference);
+    }
+}
@@ -3,13 +3,15 @@ use std::fmt;
 
 use anyhow::{anyhow, bail};
 
+use crate::Answers;
+
 /// Total disk space.
 const TOTAL_DISK_SPACE: u64 = 70_000_000;
 
 /// Needed disk space.
 const NEEDED_DISK_SPACE: u64 = 30_000_000;
 
-pub fn main(input: String) -> anyhow::Result<()> {
+pub fn main(input: String) -> anyhow::Result<Answers> {
     let mut filesystem: HashMap<String, u64> = HashMap::new();
     let mut current_dir = Directory::new();
 
@@ -33,7 +35,10 @@ pub fn main(input: String) -> anyhow::Result<()> {
                 let size: u64 = size.parse()?;
 
                 for dir in &current_dir {
-                    *filesystem.entry(dir).or_default() += size;
+                    let dir_size = filesystem.entry(dir).or_default();
+                    *dir_size = dir_size
+                        .checked_add(size)
+                        .ok_or_else(|| anyhow!("directory size overflowed adding {line}"))?;
                 }
             }
             Some("dir") => {}
@@ -47,8 +52,10 @@ pub fn main(input: String) -> anyhow::Result<()> {
     let used_space: u64 = *filesystem
         .get("/")
         .ok_or_else(|| anyhow!("failed to get filesystem used space"))?;
-    let free_space = TOTAL_DISK_SPACE - used_space;
-    let space_to_delete = NEEDED_DISK_SPACE - free_space;
+    let free_space = TOTAL_DISK_SPACE
+        .checked_sub(used_space)
+        .ok_or_else(|| anyhow!("used space {used_space} is more than the disk holds"))?;
+    let space_to_delete = NEEDED_DISK_SPACE.saturating_sub(free_space);
 
     let part2 = filesystem
         .values()
@@ -57,12 +64,12 @@ pub fn main(input: String) -> anyhow::Result<()> {
         .ok_or_else(|| anyhow!("failed to find smallest directory to delete"))?;
     log::info!("size of directory to delete: {part2}");
 
-    Ok(())
+    Ok(Answers::new(part1, part2))
 }
 
 /// A location in the filesystem to navigate to.
 #[derive(Debug)]
-enum Location {
+pub(crate) enum Location {
     Root,
     Parent,
     Directory(String),
@@ -70,7 +77,7 @@ enum Location {
 
 /// A command to run on the operating system.
 #[derive(Debug)]
-enum Command {
+pub(crate) enum Command {
     Cd { location: Location },
     Ls,
 }
@@ -164,9 +171,7 @@ impl<'a> Iterator for DirectoryHierarchy<'a> {
     type Item = String;
 
     fn next(&mut self) -> Option<Self::Item> {
-        let Some(state) = self.state else {
-            return None;
-        };
+        let state = self.state?;
 
         let next = Some(format!(
             "/{}",
@@ -198,6 +203,54 @@ impl<'a> IntoIterator for &'a Directory {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::gen;
+
+    /// Lists every file by its full path and adds up the files under each directory.
+    fn reference(input: &str) -> Answers {
+        // Paths end in a slash, so a directory's path starts every path inside it
+        let path = |cwd: &[&str]| cwd.iter().map(|dir| format!("/{dir}")).collect::<String>() + "/";
+
+        let mut cwd: Vec<&str> = vec![];
+        let mut directories = vec![String::from("/")];
+        let mut files: Vec<(String, u64)> = vec![];
+
+        for line in input.lines() {
+            let words: Vec<&str> = line.split(' ').collect();
+            match words[..] {
+                ["$", "cd", "/"] => cwd.clear(),
+                ["$", "cd", ".."] => drop(cwd.pop()),
+                ["$", "cd", dir] => {
+                    cwd.push(dir);
+                    directories.push(path(&cwd));
+                }
+                ["$", "ls"] | ["dir", _] => {}
+                [size, name] => files.push((path(&cwd) + name, size.parse().unwrap())),
+                _ => unreachable!("{line}"),
+            }
+        }
+
+        let sizes: Vec<u64> = directories
+            .iter()
+            .map(|dir| {
+                files
+                    .iter()
+                    .filter(|(file, _)| file.starts_with(dir.as_str()))
+                    .map(|(_, size)| size)
+                    .sum()
+            })
+            .collect();
+
+        let small: u64 = sizes.iter().filter(|&&size| size <= 100_000).sum();
+        let needed = sizes[0].saturating_sub(TOTAL_DISK_SPACE - NEEDED_DISK_SPACE);
+        let delete = sizes.iter().filter(|&&size| size >= needed).min().unwrap();
+
+        Answers::new(small, delete)
+    }
+
+    #[test]
+    fn matches_reference() {
+        gen::assert_matches_reference(7, main, reference);
+    }
 
     #[test]
     fn directory_new() {
@@ -211,7 +264,7 @@ mod tests {
         dir.push_path("a");
 
         assert_eq!(dir.path.len(), 1);
-        assert_eq!(dir.path.get(0).unwrap(), "a");
+        assert_eq!(dir.path.first().unwrap(), "a");
 
         dir.push_path("b");
         assert_eq!(dir.path.len(), 2);
@@ -230,12 +283,12 @@ mod tests {
     fn directory_from() {
         let dir = Directory::from(vec!["a", "b"]);
         assert_eq!(dir.path.len(), 2);
-        assert_eq!(dir.path.get(0).unwrap(), "a");
+        assert_eq!(dir.path.first().unwrap(), "a");
         assert_eq!(dir.path.get(1).unwrap(), "b");
 
         let dir = Directory::from(["t".to_string(), "u".to_string(), "v".to_string()]);
         assert_eq!(dir.path.len(), 3);
-        assert_eq!(dir.path.get(0).unwrap(), "t");
+        assert_eq!(dir.path.first().unwrap(), "t");
         assert_eq!(dir.path.get(1).unwrap(), "u");
         assert_eq!(dir.path.get(2).unwrap(), "v");
     }
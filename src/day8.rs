@@ -1,138 +1,221 @@
-pub fn main(input: String) -> anyhow::Result<()> {
-    let forest: Vec<Vec<u32>> = input
-        .lines()
-        .map(|line| line.chars().filter_map(|c| c.to_digit(10)).collect())
-        .collect();
+use anyhow::anyhow;
+
+use crate::{
+    grid::{Grid, Position},
+    runner::Variant,
+    visualize::{self, Color, Frame, Visualize},
+    Answers,
+};
+
+/// The ways of looking at the trees, the first being the default.
+pub const VARIANTS: &[Variant] = &[
+    Variant {
+        name: "scan",
+        solution: main,
+        stream: None,
+    },
+    Variant {
+        name: "stack",
+        solution: main_stack,
+        stream: None,
+    },
+];
+
+pub fn main(input: String) -> anyhow::Result<Answers> {
+    solve(&input, scan)
+}
+
+/// Looks along each row and column once, keeping a stack of the trees that could still block the
+/// view, instead of scanning from every tree.
+fn main_stack(input: String) -> anyhow::Result<Answers> {
+    solve(&input, stack)
+}
+
+/// Counts the visible trees and finds the highest scenic score with `look`.
+fn solve(input: &str, look: fn(&Grid<u32>) -> (usize, usize)) -> anyhow::Result<Answers> {
+    let forest = parse(input)?;
+    visualize::show(&Forest(&forest));
+
+    let (visible_trees, highest_scenic_score) = look(&forest);
 
     // Part 1
-    let mut visible_trees = 0;
+    log::info!("number of trees visible from outside the forest: {visible_trees}");
 
-    let rows = forest.len();
+    // Part 2
+    log::info!("highest scenic score in the forest: {highest_scenic_score}");
+
+    Ok(Answers::new(visible_trees, highest_scenic_score))
+}
 
-    for (i, row) in forest.iter().enumerate() {
-        let cols = row.len();
+/// Scans outward from every tree in all four directions. Returns the number of visible trees and
+/// the highest scenic score.
+fn scan(forest: &Grid<u32>) -> (usize, usize) {
+    // Part 1
+    // Trees on the edge have nothing in the way in at least one direction
+    let visible_trees = forest
+        .positions()
+        .filter(|&tree| {
+            let height = forest[tree];
+            forest
+                .rays(tree)
+                .any(|mut ray| ray.all(|other| forest[other] < height))
+        })
+        .count();
 
-        if i == 0 || i == rows - 1 {
-            visible_trees += cols;
-            continue;
+    // Part 2
+    let highest_scenic_score = forest
+        .positions()
+        .map(|tree| {
+            forest
+                .rays(tree)
+                .map(|ray| viewing_distance(forest, forest[tree], ray))
+                .product()
+        })
+        .max()
+        .unwrap_or_default();
+
+    (visible_trees, highest_scenic_score)
+}
+
+/// Counts the trees a tree of `height` can see along `ray`, up to and including the first one at
+/// least as tall.
+fn viewing_distance(forest: &Grid<u32>, height: u32, ray: impl Iterator<Item = Position>) -> usize {
+    let mut trees = 0;
+
+    for other in ray {
+        trees += 1;
+        if forest[other] >= height {
+            break;
         }
+    }
 
-        for (j, tree) in row.iter().enumerate() {
-            if j == 0 || j == cols - 1 {
-                visible_trees += 1;
-                continue;
-            }
+    trees
+}
 
-            // Up
-            if (0..i).all(|n| {
-                let other_height = forest.get(n).unwrap().get(j).unwrap();
-                other_height < tree
-            }) {
-                visible_trees += 1;
-                continue;
-            }
+/// Sweeps each row and column in both directions with a monotonic stack. Returns the number of
+/// visible trees and the highest scenic score.
+fn stack(forest: &Grid<u32>) -> (usize, usize) {
+    let (rows, cols) = (forest.height(), forest.width());
 
-            // Down
-            if (i + 1..rows).all(|n| {
-                let other_height = forest.get(n).unwrap().get(j).unwrap();
-                other_height < tree
-            }) {
-                visible_trees += 1;
-                continue;
-            }
+    let mut visible = Grid::new(cols, rows, false);
+    let mut scores = Grid::new(cols, rows, 1);
 
-            // Left
-            if (0..j).all(|n| {
-                let other_height = forest.get(i).unwrap().get(n).unwrap();
-                other_height < tree
-            }) {
-                visible_trees += 1;
-                continue;
-            }
+    let lines = (0..rows)
+        .map(|i| (0..cols).map(|j| (i, j)).collect::<Vec<_>>())
+        .chain((0..cols).map(|j| (0..rows).map(|i| (i, j)).collect()));
+
+    for line in lines {
+        let reversed: Vec<_> = line.iter().rev().copied().collect();
 
-            // Right
-            if (j + 1..cols).all(|n| {
-                let other_height = forest.get(i).unwrap().get(n).unwrap();
-                other_height < tree
-            }) {
-                visible_trees += 1;
-                continue;
+        for line in [line, reversed] {
+            // Positions along the line of trees not yet blocked by a taller or equal tree
+            let mut blockers: Vec<usize> = Vec::with_capacity(line.len());
+
+            for (t, &tree) in line.iter().enumerate() {
+                let height = forest[tree];
+
+                while let Some(&b) = blockers.last() {
+                    if forest[line[b]] >= height {
+                        break;
+                    }
+                    blockers.pop();
+                }
+
+                // The view stops at the nearest tree at least as tall, or at the edge
+                match blockers.last() {
+                    Some(&b) => scores[tree] *= t - b,
+                    None => {
+                        visible[tree] = true;
+                        scores[tree] *= t;
+                    }
+                }
+
+                blockers.push(t);
             }
         }
     }
 
-    log::info!("number of trees visible from outside the forest: {visible_trees}");
+    let visible_trees = visible.iter().filter(|(_, &visible)| visible).count();
+    let highest_scenic_score = scores
+        .iter()
+        .map(|(_, &score)| score)
+        .max()
+        .unwrap_or_default();
 
-    // Part 2
-    let mut highest_scenic_score = 0;
-
-    let rows = forest.len();
-
-    for (i, row) in forest.iter().enumerate() {
-        let cols = row.len();
-
-        for (j, tree) in row.iter().enumerate() {
-            // Up
-            let up_score = if i == 0 {
-                0
-            } else {
-                match (0..i).position(|n| {
-                    let other_height = forest.get(i - n - 1).unwrap().get(j).unwrap();
-                    other_height >= tree
-                }) {
-                    Some(blocking_tree_iter_pos) => blocking_tree_iter_pos + 1,
-                    None => i,
-                }
-            };
-
-            // Down
-            let down_score = if i == rows - 1 {
-                0
-            } else {
-                match (i + 1..rows).position(|n| {
-                    let other_height = forest.get(n).unwrap().get(j).unwrap();
-                    other_height >= tree
-                }) {
-                    Some(blocking_tree_iter_pos) => blocking_tree_iter_pos + 1,
-                    None => rows - i - 1,
-                }
-            };
-
-            // Left
-            let left_score = if j == 0 {
-                0
-            } else {
-                match (0..j).position(|n| {
-                    let other_height = forest.get(i).unwrap().get(j - n - 1).unwrap();
-                    other_height >= tree
-                }) {
-                    Some(blocking_tree_iter_pos) => blocking_tree_iter_pos + 1,
-                    None => j,
-                }
-            };
-
-            // Right
-            let right_score = if j == cols - 1 {
-                0
-            } else {
-                match (j + 1..cols).position(|n| {
-                    let other_height = forest.get(i).unwrap().get(n).unwrap();
-                    other_height >= tree
-                }) {
-                    Some(blocking_tree_iter_pos) => blocking_tree_iter_pos + 1,
-                    None => cols - j - 1,
-                }
-            };
+    (visible_trees, highest_scenic_score)
+}
 
-            let scenic_score = up_score * down_score * left_score * right_score;
+/// The heights of the trees, shaded from short and dark to tall and light.
+struct Forest<'a>(&'a Grid<u32>);
 
-            if scenic_score > highest_scenic_score {
-                highest_scenic_score = scenic_score;
-            }
+impl Visualize for Forest<'_> {
+    fn frame(&self) -> Frame {
+        let mut frame = Frame::new("Tree heights", self.0.width(), self.0.height());
+
+        for ((y, x), &height) in self.0.iter() {
+            let glyph = char::from_digit(height, 10).unwrap_or('?');
+            frame.set(x, y, glyph, Some(Color::shade(height, 9)));
         }
+
+        frame
     }
+}
 
-    log::info!("highest scenic score in the forest: {highest_scenic_score}");
+/// Parses the heights of the trees in the forest, one row per line.
+pub(crate) fn parse(input: &str) -> anyhow::Result<Grid<u32>> {
+    Grid::parse(input, |c| {
+        c.to_digit(10)
+            .ok_or_else(|| anyhow!("{c} is not the height of a tree"))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::gen;
+
+    /// Casts a ray from every tree in every direction.
+    fn reference(input: &str) -> Answers {
+        let forest: Vec<Vec<u8>> = input.lines().map(|line| line.bytes().collect()).collect();
+        let (rows, cols) = (forest.len() as i64, forest[0].len() as i64);
+
+        let mut visible = 0;
+        let mut best = 0;
+
+        for r in 0..rows {
+            for c in 0..cols {
+                let height = forest[r as usize][c as usize];
+                let mut seen_from_outside = false;
+                let mut score = 1;
+
+                for (dr, dc) in [(-1, 0), (1, 0), (0, -1), (0, 1)] {
+                    let (mut nr, mut nc) = (r + dr, c + dc);
+                    let mut trees = 0;
+                    let mut blocked = false;
+
+                    while (0..rows).contains(&nr) && (0..cols).contains(&nc) {
+                        trees += 1;
+                        if forest[nr as usize][nc as usize] >= height {
+                            blocked = true;
+                            break;
+                        }
+                        (nr, nc) = (nr + dr, nc + dc);
+                    }
+
+                    seen_from_outside |= !blocked;
+                    score *= trees;
+                }
 
-    Ok(())
+                visible += usize::from(seen_from_outside);
+                best = best.max(score);
+            }
+        }
+
+        Answers::new(visible, best)
+    }
+
+    #[test]
+    fn matches_reference() {
+        gen::assert_matches_reference(8, main, reference);
+    }
 }
@@ -1,363 +1,213 @@
 use std::collections::HashSet;
 
-use anyhow::bail;
+use anyhow::anyhow;
 
-pub fn main(input: String) -> anyhow::Result<()> {
-    let directions: Vec<(&str, i32)> = input
-        .lines()
-        .flat_map(|line| {
-            line.split_once(' ')
-                .map(|(direction, distance)| (direction, distance.parse().unwrap_or_default()))
-        })
-        .collect();
-
-    let mut rope = Rope::new(None);
-
-    for (direction, distance) in &directions {
-        for _ in 0..*distance {
-            let head_position = &mut rope.head.current_position;
-            let tail_position = &mut rope.tail.current_position;
-
-            match *direction {
-                "U" => {
-                    head_position.1 += 1;
-                    // The head pulls the tail
-                    if head_position.1.abs_diff(tail_position.1) > 1 {
-                        tail_position.1 += 1;
-                        // The tail will always be aligned with the head on the x axis
-                        tail_position.0 = head_position.0;
-                    }
-                }
-                "D" => {
-                    head_position.1 -= 1;
-                    // The head pulls the tail
-                    if head_position.1.abs_diff(tail_position.1) > 1 {
-                        tail_position.1 -= 1;
-                        // The tail will always be aligned with the head on the x axis
-                        tail_position.0 = head_position.0;
-                    }
-                }
-                "L" => {
-                    head_position.0 -= 1;
-                    // The head pulls the tail
-                    if head_position.0.abs_diff(tail_position.0) > 1 {
-                        tail_position.0 -= 1;
-                        // The tail will always be aligned with the head on the y axis
-                        tail_position.1 = head_position.1;
-                    }
-                }
-                "R" => {
-                    head_position.0 += 1;
-                    // The head pulls the tail
-                    if head_position.0.abs_diff(tail_position.0) > 1 {
-                        tail_position.0 += 1;
-                        // The tail will always be aligned with the head on the y axis
-                        tail_position.1 = head_position.1;
-                    }
-                }
-                _ => bail!("{direction} is invalid"),
-            }
+use crate::{
+    geometry::{BoundingBox, Direction, Point},
+    visualize::{self, Color, Frame, Visualize},
+    Answers,
+};
 
-            rope.head.position_history.insert(*head_position);
-            rope.tail.position_history.insert(*tail_position);
-        }
-    }
+pub fn main(input: String) -> anyhow::Result<Answers> {
+    let motions = parse(&input)?;
 
-    log::info!(
-        "part 1 - number of positions the tail visited at least once: {}",
-        rope.tail.position_history.len()
-    );
+    let mut rope = Rope::new(2);
+    rope.make(&motions);
 
-    let mut rope2 = Rope2::new(10);
-
-    for (direction, distance) in &directions {
-        for _ in 0..*distance {
-            match *direction {
-                "U" => {
-                    for i in 0..rope2.nodes.len() {
-                        if i == 0 {
-                            let head_node = rope2.nodes.get_mut(i).unwrap();
-                            head_node.current_position.1 += 1;
-                            head_node
-                                .position_history
-                                .insert(head_node.current_position);
-                        } else {
-                            let prior_node_position =
-                                rope2.nodes.get(i - 1).unwrap().current_position;
-                            let current_node = rope2.nodes.get_mut(i).unwrap();
-                            if more_than_one_apart(
-                                prior_node_position,
-                                current_node.current_position,
-                            ) {
-                                if prior_node_position.0 - current_node.current_position.0 > 1 {
-                                    current_node.current_position.0 += 1;
-                                } else if current_node.current_position.0 - prior_node_position.0
-                                    > 1
-                                {
-                                    current_node.current_position.0 -= 1;
-                                } else if current_node
-                                    .current_position
-                                    .0
-                                    .abs_diff(prior_node_position.0)
-                                    == 1
-                                {
-                                    current_node.current_position.0 = prior_node_position.0;
-                                }
-                                if prior_node_position.1 - current_node.current_position.1 > 1 {
-                                    current_node.current_position.1 += 1;
-                                } else if current_node.current_position.1 - prior_node_position.1
-                                    > 1
-                                {
-                                    current_node.current_position.1 -= 1;
-                                } else if current_node
-                                    .current_position
-                                    .1
-                                    .abs_diff(prior_node_position.1)
-                                    == 1
-                                {
-                                    current_node.current_position.1 = prior_node_position.1;
-                                }
-
-                                current_node
-                                    .position_history
-                                    .insert(current_node.current_position);
-                            } else {
-                                break;
-                            }
-                        }
-                    }
-                }
-                "D" => {
-                    for i in 0..rope2.nodes.len() {
-                        if i == 0 {
-                            let head_node = rope2.nodes.get_mut(i).unwrap();
-                            head_node.current_position.1 -= 1;
-                            head_node
-                                .position_history
-                                .insert(head_node.current_position);
-                        } else {
-                            let prior_node_position =
-                                rope2.nodes.get(i - 1).unwrap().current_position;
-                            let current_node = rope2.nodes.get_mut(i).unwrap();
-                            if more_than_one_apart(
-                                prior_node_position,
-                                current_node.current_position,
-                            ) {
-                                if prior_node_position.0 - current_node.current_position.0 > 1 {
-                                    current_node.current_position.0 += 1;
-                                } else if current_node.current_position.0 - prior_node_position.0
-                                    > 1
-                                {
-                                    current_node.current_position.0 -= 1;
-                                } else if current_node
-                                    .current_position
-                                    .0
-                                    .abs_diff(prior_node_position.0)
-                                    == 1
-                                {
-                                    current_node.current_position.0 = prior_node_position.0;
-                                }
-                                if prior_node_position.1 - current_node.current_position.1 > 1 {
-                                    current_node.current_position.1 += 1;
-                                } else if current_node.current_position.1 - prior_node_position.1
-                                    > 1
-                                {
-                                    current_node.current_position.1 -= 1;
-                                } else if current_node
-                                    .current_position
-                                    .1
-                                    .abs_diff(prior_node_position.1)
-                                    == 1
-                                {
-                                    current_node.current_position.1 = prior_node_position.1;
-                                }
-
-                                current_node
-                                    .position_history
-                                    .insert(current_node.current_position);
-                            } else {
-                                break;
-                            }
-                        }
-                    }
-                }
-                "L" => {
-                    for i in 0..rope2.nodes.len() {
-                        if i == 0 {
-                            let head_node = rope2.nodes.get_mut(i).unwrap();
-                            head_node.current_position.0 -= 1;
-                            head_node
-                                .position_history
-                                .insert(head_node.current_position);
-                        } else {
-                            let prior_node_position =
-                                rope2.nodes.get(i - 1).unwrap().current_position;
-                            let current_node = rope2.nodes.get_mut(i).unwrap();
-                            if more_than_one_apart(
-                                prior_node_position,
-                                current_node.current_position,
-                            ) {
-                                if prior_node_position.0 - current_node.current_position.0 > 1 {
-                                    current_node.current_position.0 += 1;
-                                } else if current_node.current_position.0 - prior_node_position.0
-                                    > 1
-                                {
-                                    current_node.current_position.0 -= 1;
-                                } else if current_node
-                                    .current_position
-                                    .0
-                                    .abs_diff(prior_node_position.0)
-                                    == 1
-                                {
-                                    current_node.current_position.0 = prior_node_position.0;
-                                }
-
-                                if prior_node_position.1 - current_node.current_position.1 > 1 {
-                                    current_node.current_position.1 += 1;
-                                } else if current_node.current_position.1 - prior_node_position.1
-                                    > 1
-                                {
-                                    current_node.current_position.1 -= 1;
-                                } else if current_node
-                                    .current_position
-                                    .1
-                                    .abs_diff(prior_node_position.1)
-                                    == 1
-                                {
-                                    current_node.current_position.1 = prior_node_position.1;
-                                }
-                                current_node
-                                    .position_history
-                                    .insert(current_node.current_position);
-                            } else {
-                                break;
-                            }
-                        }
-                    }
-                }
-                "R" => {
-                    for i in 0..rope2.nodes.len() {
-                        if i == 0 {
-                            let head_node = rope2.nodes.get_mut(i).unwrap();
-                            head_node.current_position.0 += 1;
-                            head_node
-                                .position_history
-                                .insert(head_node.current_position);
-                        } else {
-                            let prior_node_position =
-                                rope2.nodes.get(i - 1).unwrap().current_position;
-                            let current_node = rope2.nodes.get_mut(i).unwrap();
-                            if more_than_one_apart(
-                                prior_node_position,
-                                current_node.current_position,
-                            ) {
-                                if prior_node_position.0 - current_node.current_position.0 > 1 {
-                                    current_node.current_position.0 += 1;
-                                } else if current_node.current_position.0 - prior_node_position.0
-                                    > 1
-                                {
-                                    current_node.current_position.0 -= 1;
-                                } else if current_node
-                                    .current_position
-                                    .0
-                                    .abs_diff(prior_node_position.0)
-                                    == 1
-                                {
-                                    current_node.current_position.0 = prior_node_position.0;
-                                }
-
-                                if prior_node_position.1 - current_node.current_position.1 > 1 {
-                                    current_node.current_position.1 += 1;
-                                } else if current_node.current_position.1 - prior_node_position.1
-                                    > 1
-                                {
-                                    current_node.current_position.1 -= 1;
-                                } else if current_node
-                                    .current_position
-                                    .1
-                                    .abs_diff(prior_node_position.1)
-                                    == 1
-                                {
-                                    current_node.current_position.1 = prior_node_position.1;
-                                }
-
-                                current_node
-                                    .position_history
-                                    .insert(current_node.current_position);
-                            } else {
-                                break;
-                            }
-                        }
-                    }
-                }
-                _ => bail!("{direction} is invalid"),
-            }
-        }
-    }
+    let part1 = rope.visited_by_tail();
+    log::info!("part 1 - number of positions the tail visited at least once: {part1}");
 
-    log::info!(
-        "part2 - number of positions the tail visited at least once: {}",
-        rope2.nodes.last().unwrap().position_history.len()
-    );
+    let mut rope2 = Rope::new(10);
+    rope2.make(&motions);
+
+    let part2 = rope2.visited_by_tail();
+    log::info!("part2 - number of positions the tail visited at least once: {part2}");
 
-    Ok(())
+    Ok(Answers::new(part1, part2))
+}
+
+/// Parses the motions of the head, one direction and distance per line.
+pub(crate) fn parse(input: &str) -> anyhow::Result<Vec<(Direction, u32)>> {
+    input
+        .lines()
+        .map(|line| {
+            let (direction, distance) = line
+                .split_once(' ')
+                .ok_or_else(|| anyhow!("{line} is not a direction and a distance"))?;
+            Ok((direction.parse()?, distance.parse()?))
+        })
+        .collect()
 }
 
 /// A node in the rope (e.g. head, tail).
 #[derive(Debug, Clone)]
 struct Node {
-    current_position: (i32, i32),
-    position_history: HashSet<(i32, i32)>,
+    current_position: Point,
+    position_history: HashSet<Point>,
 }
 
-/// A rope with a head and tail.
+/// A rope with an arbitrary number of nodes, the head first.
 #[derive(Debug)]
 struct Rope {
-    head: Node,
-    tail: Node,
+    nodes: Vec<Node>,
 }
 
 impl Rope {
-    /// Default starting position of the rope's head and tail.
-    const DEFAULT_START_POSITION: (i32, i32) = (0, 0);
-
-    /// Creates a new rope.
-    fn new(start_position: Option<(i32, i32)>) -> Rope {
-        let start_position = start_position.unwrap_or(Self::DEFAULT_START_POSITION);
+    /// Constructs a rope with `n` nodes, all starting at the origin.
+    fn new(n: usize) -> Rope {
         Rope {
-            head: Node {
-                current_position: start_position,
-                position_history: HashSet::from([start_position]),
-            },
-            tail: Node {
-                current_position: start_position,
-                position_history: HashSet::from([(start_position)]),
-            },
+            nodes: vec![
+                Node {
+                    current_position: Point::ORIGIN,
+                    position_history: HashSet::from([Point::ORIGIN]),
+                };
+                n
+            ],
+        }
+    }
+
+    /// The number of positions the last node of the rope has visited.
+    fn visited_by_tail(&self) -> usize {
+        self.nodes
+            .last()
+            .map_or(0, |tail| tail.position_history.len())
+    }
+
+    /// Moves the head through all of `motions`, one step at a time.
+    fn make(&mut self, motions: &[(Direction, u32)]) {
+        for &(direction, distance) in motions {
+            for _ in 0..distance {
+                self.step(direction);
+                visualize::show(self);
+            }
+        }
+    }
+
+    /// Moves the head one step in `direction`, each node pulling the next one along.
+    fn step(&mut self, direction: Direction) {
+        let mut leader: Option<Point> = None;
+
+        for node in &mut self.nodes {
+            match leader {
+                None => node.current_position += direction.vector(),
+                // A node touching the one ahead of it stays put, and so does the rest of the rope
+                Some(leader) if leader.chebyshev(node.current_position) <= 1 => break,
+                // Otherwise it moves one step straight or diagonally towards it
+                Some(leader) => node.current_position += (leader - node.current_position).signum(),
+            }
+
+            node.position_history.insert(node.current_position);
+            leader = Some(node.current_position);
         }
     }
 }
 
-/// A rope with an arbitrary number of nodes.
-#[derive(Debug)]
-struct Rope2 {
-    nodes: Vec<Node>,
+impl Visualize for Rope {
+    fn frame(&self) -> Frame {
+        let labels = ['H', '1', '2', '3', '4', '5', '6', '7', '8', '9'];
+        let knots: Vec<_> = match self.nodes.len() {
+            2 => vec![('H', &self.nodes[0]), ('T', &self.nodes[1])],
+            _ => labels.into_iter().zip(&self.nodes).collect(),
+        };
+
+        draw(&knots)
+    }
 }
 
-impl Rope2 {
-    /// Constructs a rope with `n` nodes.
-    fn new(n: usize) -> Rope2 {
-        Rope2 {
-            nodes: vec![
-                Node {
-                    current_position: (0, 0),
-                    position_history: HashSet::from([(0, 0)]),
-                };
-                n
-            ],
+/// Draws the `knots` of a rope with their labels, over the squares the last knot has visited.
+/// Knots earlier in the list are drawn on top, the way the puzzle does.
+fn draw(knots: &[(char, &Node)]) -> Frame {
+    let Some((_, tail)) = knots.last() else {
+        return Frame::default();
+    };
+
+    // The frame fits the start, the knots and everywhere the tail has been
+    let points = knots
+        .iter()
+        .map(|(_, knot)| knot.current_position)
+        .chain(tail.position_history.iter().copied());
+    let bounds = points.fold(BoundingBox::new(Point::ORIGIN), BoundingBox::including);
+
+    let title = format!(
+        "Rope of {} knots, the tail has visited {} positions",
+        knots.len(),
+        tail.position_history.len()
+    );
+    let (width, height) = (bounds.width() as usize, bounds.height() as usize);
+    let mut frame = Frame::new(title, width, height);
+
+    // Up is the top of the frame
+    let cell = |point: Point| {
+        (
+            point.x.abs_diff(bounds.min.x) as usize,
+            point.y.abs_diff(bounds.max.y) as usize,
+        )
+    };
+
+    for x in 0..width {
+        for y in 0..height {
+            frame.set(x, y, '.', Some(Color::Grey));
         }
     }
+    for &point in &tail.position_history {
+        let (x, y) = cell(point);
+        frame.set(x, y, '#', Some(Color::Blue));
+    }
+    let (x, y) = cell(Point::ORIGIN);
+    frame.set(x, y, 's', Some(Color::Green));
+    for &(label, knot) in knots.iter().rev() {
+        let (x, y) = cell(knot.current_position);
+        let color = if label == 'H' {
+            Color::Red
+        } else {
+            Color::Yellow
+        };
+        frame.set(x, y, label, Some(color));
+    }
+
+    frame
 }
 
-fn more_than_one_apart(rhs: (i32, i32), lhs: (i32, i32)) -> bool {
-    rhs.0.abs_diff(lhs.0) > 1 || rhs.1.abs_diff(lhs.1) > 1
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::gen;
+
+    /// Moves every knot of a ten knot rope one step at a time.
+    fn reference(input: &str) -> Answers {
+        let mut knots = [(0i32, 0i32); 10];
+        let mut second = HashSet::from([(0, 0)]);
+        let mut last = HashSet::from([(0, 0)]);
+
+        for line in input.lines() {
+            let (direction, distance) = line.split_once(' ').unwrap();
+            let (dx, dy) = match direction {
+                "U" => (0, 1),
+                "D" => (0, -1),
+                "L" => (-1, 0),
+                _ => (1, 0),
+            };
+
+            for _ in 0..distance.parse::<u32>().unwrap() {
+                knots[0] = (knots[0].0 + dx, knots[0].1 + dy);
+
+                for i in 1..knots.len() {
+                    let (x, y) = (knots[i - 1].0 - knots[i].0, knots[i - 1].1 - knots[i].1);
+                    if x.abs() > 1 || y.abs() > 1 {
+                        knots[i] = (knots[i].0 + x.signum(), knots[i].1 + y.signum());
+                    }
+                }
+
+                second.insert(knots[1]);
+                last.insert(knots[9]);
+            }
+        }
+
+        Answers::new(second.len(), last.len())
+    }
+
+    #[test]
+    fn matches_reference() {
+        gen::assert_matches_reference(9, main, reference);
+    }
 }
@@ -0,0 +1,79 @@
+use std::{
+    cell::RefCell,
+    sync::atomic::{AtomicBool, Ordering},
+};
+
+/// Set by `--explain`.
+static ENABLED: AtomicBool = AtomicBool::new(false);
+
+thread_local! {
+    /// The narrative so far, while [`capture`] is collecting it on this thread.
+    static CAPTURED: RefCell<Option<String>> = const { RefCell::new(None) };
+}
+
+/// Turns explain mode on or off for every thread.
+pub fn enable(enabled: bool) {
+    ENABLED.store(enabled, Ordering::Relaxed);
+}
+
+/// Is explain mode on? Solutions can check this before doing extra work for their narrative.
+pub fn enabled() -> bool {
+    ENABLED.load(Ordering::Relaxed) || CAPTURED.with(|captured| captured.borrow().is_some())
+}
+
+/// Adds a line to the narrative. It goes to stderr, keeping stdout free for answers.
+pub fn line(line: &str) {
+    let printed = CAPTURED.with(|captured| match captured.borrow_mut().as_mut() {
+        Some(narrative) => {
+            narrative.push_str(line);
+            narrative.push('\n');
+            false
+        }
+        None => true,
+    });
+
+    if printed {
+        eprintln!("{line}");
+    }
+}
+
+/// Runs `f` with explain mode on for this thread, collecting the narrative instead of printing it.
+pub fn capture<T>(f: impl FnOnce() -> T) -> (T, String) {
+    let outer = CAPTURED.with(|captured| captured.borrow_mut().replace(String::new()));
+    let result = f();
+    let narrative = CAPTURED.with(|captured| captured.replace(outer));
+
+    (result, narrative.unwrap_or_default())
+}
+
+/// Adds a line to the narrative if explain mode is on, formatting it like `format!`.
+macro_rules! narrate {
+    ($($arg:tt)*) => {
+        if $crate::explain::enabled() {
+            $crate::explain::line(&format!($($arg)*));
+        }
+    };
+}
+
+pub(crate) use narrate;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn capture_narrative() {
+        assert!(!enabled());
+
+        let (answer, narrative) = capture(|| {
+            assert!(enabled());
+            narrate!("step {}", 1);
+            narrate!("step {}", 2);
+            42
+        });
+
+        assert_eq!(answer, 42);
+        assert_eq!(narrative, "step 1\nstep 2\n");
+        assert!(!enabled());
+    }
+}
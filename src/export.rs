@@ -0,0 +1,202 @@
+use std::{
+    fs::File,
+    io::{BufWriter, Write},
+    path::Path,
+};
+
+use anyhow::{anyhow, bail};
+
+use crate::visualize::{Cell, Frame};
+
+/// How many pixels wide and high each cell of a frame is drawn.
+const SCALE: usize = 8;
+
+/// The color of empty cells, like `' '` and `'.'`, that have no color of their own.
+const BACKGROUND: [u8; 3] = [15, 15, 35];
+
+/// The color of other cells without a color of their own.
+const FOREGROUND: [u8; 3] = [204, 204, 204];
+
+/// The kinds of image file frames can be written to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    Ppm,
+    Png,
+    Gif,
+}
+
+impl Format {
+    /// Picks the format from the extension of `path`.
+    pub fn of(path: &Path) -> anyhow::Result<Format> {
+        let extension = path
+            .extension()
+            .and_then(|extension| extension.to_str())
+            .map(str::to_ascii_lowercase);
+
+        match extension.as_deref() {
+            Some("ppm") => Ok(Format::Ppm),
+            Some("png") => Ok(Format::Png),
+            Some("gif") => Ok(Format::Gif),
+            _ => bail!(
+                "cannot tell the image format of {}, expected .ppm, .png or .gif",
+                path.display()
+            ),
+        }
+    }
+}
+
+/// An image in 8-bit RGB, row by row from the top left.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Image {
+    pub width: usize,
+    pub height: usize,
+    pub pixels: Vec<u8>,
+}
+
+impl Image {
+    /// Draws `frame` as a block of color per cell, on a canvas at least `width` by `height` cells
+    /// so that every frame of an animation is the same size. The title is left out.
+    pub fn of(frame: &Frame, width: usize, height: usize) -> Image {
+        let width = width.max(frame.width()).max(1) * SCALE;
+        let height = height.max(frame.height()).max(1) * SCALE;
+        let mut pixels = BACKGROUND.repeat(width * height);
+
+        for (y, row) in frame.rows().iter().enumerate() {
+            for (x, &cell) in row.iter().enumerate() {
+                let color = rgb(cell);
+                for py in y * SCALE..(y + 1) * SCALE {
+                    let start = (py * width + x * SCALE) * 3;
+                    for pixel in pixels[start..start + SCALE * 3].chunks_exact_mut(3) {
+                        pixel.copy_from_slice(&color);
+                    }
+                }
+            }
+        }
+
+        Image {
+            width,
+            height,
+            pixels,
+        }
+    }
+}
+
+/// The color a cell is drawn in.
+fn rgb(cell: Cell) -> [u8; 3] {
+    match (cell.color, cell.glyph) {
+        (Some(color), _) => color.rgb(),
+        (None, ' ' | '.') => BACKGROUND,
+        (None, _) => FOREGROUND,
+    }
+}
+
+/// Writes `frames` to an image at `path`, in the format its extension names. A GIF plays every
+/// frame at `fps` frames per second, while the other formats are stills of the last frame.
+pub fn save(path: &Path, frames: &[Frame], fps: u32) -> anyhow::Result<()> {
+    let format = Format::of(path)?;
+    let Some(last) = frames.last() else {
+        bail!("nothing was drawn to save to {}", path.display());
+    };
+
+    let mut file = BufWriter::new(File::create(path)?);
+
+    match format {
+        Format::Ppm => ppm(&mut file, &Image::of(last, 0, 0))?,
+        Format::Png => png(&mut file, &Image::of(last, 0, 0))?,
+        Format::Gif => gif(&mut file, frames, fps)?,
+    }
+
+    file.flush()?;
+    Ok(())
+}
+
+/// Writes a binary PPM image.
+pub fn ppm(out: &mut impl Write, image: &Image) -> anyhow::Result<()> {
+    write!(out, "P6\n{} {}\n255\n", image.width, image.height)?;
+    out.write_all(&image.pixels)?;
+    Ok(())
+}
+
+/// Writes a PNG image.
+pub fn png(out: &mut impl Write, image: &Image) -> anyhow::Result<()> {
+    let mut encoder = png::Encoder::new(out, image.width.try_into()?, image.height.try_into()?);
+    encoder.set_color(png::ColorType::Rgb);
+    encoder.set_depth(png::BitDepth::Eight);
+
+    let mut writer = encoder.write_header()?;
+    writer.write_image_data(&image.pixels)?;
+    writer.finish()?;
+    Ok(())
+}
+
+/// Writes an animated GIF that loops forever, playing `fps` frames per second.
+pub fn gif(out: &mut impl Write, frames: &[Frame], fps: u32) -> anyhow::Result<()> {
+    // Frames can grow as the solution goes, but a GIF is one size throughout
+    let width = frames.iter().map(Frame::width).max().unwrap_or_default();
+    let height = frames.iter().map(Frame::height).max().unwrap_or_default();
+
+    let size =
+        |pixels: usize| u16::try_from(pixels).map_err(|_| anyhow!("frames are too big for a GIF"));
+    let (image_width, image_height) = (size(width.max(1) * SCALE)?, size(height.max(1) * SCALE)?);
+
+    let mut encoder = gif::Encoder::new(out, image_width, image_height, &[])?;
+    encoder.set_repeat(gif::Repeat::Infinite)?;
+
+    // GIFs count delays in hundredths of a second
+    let delay = u16::try_from(100 / fps.max(1)).unwrap_or(u16::MAX);
+
+    for frame in frames {
+        let image = Image::of(frame, width, height);
+        let mut gif_frame =
+            gif::Frame::from_rgb_speed(image_width, image_height, &image.pixels, 10);
+        gif_frame.delay = delay;
+        encoder.write_frame(&gif_frame)?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::visualize::Color;
+
+    #[test]
+    fn pick_format() {
+        assert_eq!(Format::of(Path::new("rope.GIF")).unwrap(), Format::Gif);
+        assert_eq!(Format::of(Path::new("out/crt.png")).unwrap(), Format::Png);
+        assert!(Format::of(Path::new("crt")).is_err());
+    }
+
+    #[test]
+    fn draw_cells() {
+        let mut frame = Frame::from_text("title", "#.\n");
+        frame.paint(1, 0, Color::Red);
+
+        let image = Image::of(&frame, 3, 1);
+        assert_eq!((image.width, image.height), (3 * SCALE, SCALE));
+
+        let pixel = |x: usize, y: usize| &image.pixels[(y * image.width + x) * 3..][..3];
+        assert_eq!(pixel(0, 0), FOREGROUND);
+        assert_eq!(pixel(SCALE, SCALE - 1), Color::Red.rgb());
+        assert_eq!(pixel(2 * SCALE, 0), BACKGROUND);
+
+        let mut ppm_bytes = Vec::new();
+        ppm(&mut ppm_bytes, &image).unwrap();
+        assert!(ppm_bytes.starts_with(b"P6\n24 8\n255\n"));
+        assert_eq!(ppm_bytes.len(), 12 + image.pixels.len());
+    }
+
+    #[test]
+    fn encode_images() {
+        let frames = [Frame::from_text("1", "#"), Frame::from_text("2", "##\n.#")];
+
+        let mut png_bytes = Vec::new();
+        png(&mut png_bytes, &Image::of(&frames[1], 0, 0)).unwrap();
+        assert!(png_bytes.starts_with(b"\x89PNG"));
+
+        let mut gif_bytes = Vec::new();
+        gif(&mut gif_bytes, &frames, 10).unwrap();
+        assert!(gif_bytes.starts_with(b"GIF89a"));
+    }
+}
@@ -0,0 +1,250 @@
+use std::panic::{self, AssertUnwindSafe};
+
+use crate::{day1, day10, day11, day12, day2, day3, day4, day5, day7, day8, day9, rng::Rng};
+
+/// A puzzle input parser to fuzz.
+pub struct Target {
+    /// The name to pick the target by.
+    pub name: &'static str,
+    /// The example input that seeds the corpus.
+    example: &'static str,
+    /// How the example is cut up into seeds.
+    seeds: Seeds,
+    /// Runs the parser, ignoring whatever it returns. Only panics count.
+    parse: fn(&str),
+}
+
+/// How an example input is cut up into the inputs a parser takes.
+#[derive(Debug, Clone, Copy)]
+enum Seeds {
+    /// The parser takes a whole input.
+    Whole,
+    /// The parser takes one line at a time.
+    Lines,
+    /// The parser takes one paragraph at a time.
+    Paragraphs,
+}
+
+/// Every parser there is a target for.
+pub const TARGETS: &[Target] = &[
+    Target {
+        name: "day1",
+        example: include_str!("../tests/examples/day1.txt"),
+        seeds: Seeds::Whole,
+        parse: |input| drop(day1::parse(input)),
+    },
+    Target {
+        name: "day2",
+        example: include_str!("../tests/examples/day2.txt"),
+        seeds: Seeds::Whole,
+        parse: |input| drop(day2::parse(input)),
+    },
+    Target {
+        name: "day3",
+        example: include_str!("../tests/examples/day3.txt"),
+        seeds: Seeds::Whole,
+        parse: |input| drop(day3::parse(input)),
+    },
+    Target {
+        name: "day4",
+        example: include_str!("../tests/examples/day4.txt"),
+        seeds: Seeds::Whole,
+        parse: |input| drop(day4::parse(input)),
+    },
+    Target {
+        name: "day5-instruction",
+        example: include_str!("../tests/examples/day5.txt"),
+        seeds: Seeds::Lines,
+        parse: |input| drop(day5::Instruction::try_from(input)),
+    },
+    Target {
+        name: "day7-command",
+        example: include_str!("../tests/examples/day7.txt"),
+        seeds: Seeds::Lines,
+        parse: |input| drop(day7::Command::try_from(input)),
+    },
+    Target {
+        name: "day8",
+        example: include_str!("../tests/examples/day8.txt"),
+        seeds: Seeds::Whole,
+        parse: |input| drop(day8::parse(input)),
+    },
+    Target {
+        name: "day9",
+        example: include_str!("../tests/examples/day9.txt"),
+        seeds: Seeds::Whole,
+        parse: |input| drop(day9::parse(input)),
+    },
+    Target {
+        name: "day10-instruction",
+        example: include_str!("../tests/examples/day10.txt"),
+        seeds: Seeds::Lines,
+        parse: |input| drop(day10::Instruction::try_from(input)),
+    },
+    Target {
+        name: "day11-monkey",
+        example: include_str!("../tests/examples/day11.txt"),
+        seeds: Seeds::Paragraphs,
+        parse: |input| drop(day11::Monkey::try_from(input)),
+    },
+    Target {
+        name: "day12",
+        example: include_str!("../tests/examples/day12.txt"),
+        seeds: Seeds::Whole,
+        parse: |input| drop(day12::parse(input)),
+    },
+];
+
+/// Characters that mutations insert, weighted toward those found in puzzle inputs.
+const CHARS: &[char] = &[
+    '\n', '\n', ' ', ' ', ',', '-', '+', '*', '=', ':', '[', ']', '$', '/', '.', '0', '1', '9',
+    'a', 'z', 'A', 'S', 'E', 'X', '\0', '\t', '\r', 'é', 'ÿ', '€', '🎄',
+];
+
+/// Tokens that mutations insert, chosen to sit on the edges of the parsers' number types.
+const TOKENS: &[&str] = &[
+    "0",
+    "-1",
+    "255",
+    "256",
+    "2147483647",
+    "-2147483648",
+    "4294967295",
+    "4294967296",
+    "9223372036854775807",
+    "-9223372036854775808",
+    "18446744073709551615",
+    "18446744073709551616",
+    "\n\n",
+    "  ",
+];
+
+impl Target {
+    /// Finds the target called `name`.
+    pub fn find(name: &str) -> Option<&'static Target> {
+        TARGETS.iter().find(|target| target.name == name)
+    }
+
+    /// The inputs that fuzzing starts from, cut from the example.
+    pub fn corpus(&self) -> Vec<&'static str> {
+        let mut corpus = vec![""];
+
+        match self.seeds {
+            Seeds::Whole => corpus.push(self.example),
+            Seeds::Lines => corpus.extend(self.example.lines()),
+            Seeds::Paragraphs => corpus.extend(self.example.split("\n\n")),
+        }
+
+        corpus
+    }
+
+    /// Runs the parser on `iterations` inputs, each made by mutating a seed from the corpus.
+    /// Returns the inputs that made it panic.
+    pub fn fuzz(&self, rng: &mut Rng, iterations: usize) -> Vec<String> {
+        let corpus = self.corpus();
+        let mut crashes = vec![];
+
+        for _ in 0..iterations {
+            let seed = rng.choose(&corpus).copied().unwrap_or_default();
+            let mut input: Vec<char> = seed.chars().collect();
+
+            for _ in 0..=rng.below(4) {
+                mutate(&mut input, &corpus, rng);
+            }
+
+            let input: String = input.into_iter().collect();
+            let parse = self.parse;
+
+            if panic::catch_unwind(AssertUnwindSafe(|| parse(&input))).is_err() {
+                crashes.push(input);
+            }
+        }
+
+        crashes
+    }
+}
+
+/// Makes one random change to `input`.
+fn mutate(input: &mut Vec<char>, corpus: &[&str], rng: &mut Rng) {
+    let at = rng.below(input.len() + 1);
+    let len = rng.below(input.len() - at + 1).min(16);
+
+    match rng.below(6) {
+        // Insert a character
+        0 => input.insert(at, *rng.choose(CHARS).unwrap_or(&' ')),
+        // Insert a token
+        1 => {
+            let token = rng.choose(TOKENS).copied().unwrap_or_default();
+            input.splice(at..at, token.chars());
+        }
+        // Replace a character
+        2 => {
+            if let Some(c) = input.get_mut(at) {
+                *c = *rng.choose(CHARS).unwrap_or(&' ');
+            }
+        }
+        // Delete a run of characters
+        3 => drop(input.drain(at..at + len)),
+        // Repeat a run of characters
+        4 => {
+            let run: Vec<char> = input[at..at + len].to_vec();
+            input.splice(at..at, run);
+        }
+        // Splice in part of another seed
+        _ => {
+            let other: Vec<char> = rng
+                .choose(corpus)
+                .copied()
+                .unwrap_or_default()
+                .chars()
+                .collect();
+            let from = rng.below(other.len() + 1);
+            let to = from + rng.below(other.len() - from + 1);
+            input.splice(at..at, other[from..to].iter().copied());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parsers_do_not_panic() {
+        let mut rng = Rng::new(2022);
+
+        for target in TARGETS {
+            let crashes = target.fuzz(&mut rng, 2_000);
+            assert!(
+                crashes.is_empty(),
+                "{} panicked on {crashes:?}",
+                target.name
+            );
+        }
+    }
+
+    #[test]
+    fn finds_panics() {
+        let target = Target {
+            name: "newline",
+            example: "abc",
+            seeds: Seeds::Whole,
+            parse: |input| assert!(!input.contains('\n')),
+        };
+
+        let crashes = target.fuzz(&mut Rng::new(2022), 1_000);
+        assert!(!crashes.is_empty());
+        assert!(crashes.iter().all(|input| input.contains('\n')));
+    }
+
+    #[test]
+    fn corpus_is_seeded_from_examples() {
+        let target = Target::find("day5-instruction").unwrap();
+        assert!(target.corpus().contains(&"move 1 from 2 to 1"));
+
+        let target = Target::find("day11-monkey").unwrap();
+        assert_eq!(target.corpus().len(), 5);
+
+        assert!(Target::find("day6").is_none());
+    }
+}
@@ -0,0 +1,528 @@
+use anyhow::bail;
+
+use crate::rng::Rng;
+
+/// Makes up a valid puzzle input for `day`. `size` says roughly how big it is, e.g. the number of
+/// elves, lines or moves.
+pub fn input(day: u8, rng: &mut Rng, size: usize) -> anyhow::Result<String> {
+    let size = size.max(1);
+
+    let input = match day {
+        1 => calories(rng, size),
+        2 => strategy_guide(rng, size),
+        3 => rucksacks(rng, size),
+        4 => section_assignments(rng, size),
+        5 => crate_stacks(rng, size),
+        6 => datastream(rng, size),
+        7 => terminal_output(rng, size),
+        8 => forest(rng, size),
+        9 => rope_motions(rng, size),
+        10 => program(rng, size),
+        11 => monkey_notes(rng, size),
+        12 => heightmap(rng, size),
+        _ => bail!("no input generator for day {day}"),
+    };
+
+    Ok(input)
+}
+
+/// The letters that lowercase and uppercase items can be.
+fn letters() -> Vec<char> {
+    ('a'..='z').chain('A'..='Z').collect()
+}
+
+/// Day 1: `size` elves, each carrying a few snacks.
+fn calories(rng: &mut Rng, size: usize) -> String {
+    let elves: Vec<String> = (0..size)
+        .map(|_| {
+            (0..rng.between(1, 6))
+                .map(|_| format!("{}\n", rng.between(1000, 60_000)))
+                .collect()
+        })
+        .collect();
+
+    elves.join("\n")
+}
+
+/// Day 2: `size` rounds of rock paper scissors.
+fn strategy_guide(rng: &mut Rng, size: usize) -> String {
+    (0..size)
+        .map(|_| {
+            let opponent = rng.choose(&['A', 'B', 'C']).copied().unwrap_or('A');
+            let you = rng.choose(&['X', 'Y', 'Z']).copied().unwrap_or('X');
+            format!("{opponent} {you}\n")
+        })
+        .collect()
+}
+
+/// Day 3: `size` rucksacks, rounded up to whole groups of three. The compartments of each rucksack
+/// share exactly one item type, and so do the rucksacks of each group.
+fn rucksacks(rng: &mut Rng, size: usize) -> String {
+    let mut input = String::new();
+
+    for _ in 0..size.div_ceil(3) {
+        let mut letters = letters();
+        rng.shuffle(&mut letters);
+
+        // Each elf of the group gets their own letters apart from the badge, so the badge is the
+        // only item type all three carry
+        let Some((&badge, rest)) = letters.split_first() else {
+            continue;
+        };
+
+        for pool in rest.chunks(rest.len() / 3) {
+            let mut pool = pool.to_vec();
+            let shared = match pool.pop() {
+                Some(item) if !rng.one_in(4) => item,
+                _ => badge,
+            };
+
+            let (first, second) = pool.split_at(pool.len() / 2);
+            let (mut first_must, mut second_must) = (vec![shared], vec![shared]);
+            if badge != shared {
+                if rng.one_in(2) {
+                    first_must.push(badge);
+                } else {
+                    second_must.push(badge);
+                }
+            }
+
+            let len = rng.between(2, 16);
+            input.push_str(&compartment(rng, first, &first_must, len));
+            input.push_str(&compartment(rng, second, &second_must, len));
+            input.push('\n');
+        }
+    }
+
+    input
+}
+
+/// Fills a compartment of `len` items with the `must` items and the rest picked from `items`.
+fn compartment(rng: &mut Rng, items: &[char], must: &[char], len: usize) -> String {
+    let mut compartment = must.to_vec();
+    while compartment.len() < len {
+        compartment.extend(rng.choose(items));
+    }
+
+    rng.shuffle(&mut compartment);
+    compartment.into_iter().collect()
+}
+
+/// Day 4: `size` pairs of section assignments.
+fn section_assignments(rng: &mut Rng, size: usize) -> String {
+    (0..size)
+        .map(|_| {
+            let mut range = || {
+                let start = rng.between(1, 99);
+                (start, rng.between(start, 99))
+            };
+            let (first, second) = (range(), range());
+            format!("{}-{},{}-{}\n", first.0, first.1, second.0, second.1)
+        })
+        .collect()
+}
+
+/// Day 5: nine stacks of crates and `size` moves, none of which takes more crates than there are.
+fn crate_stacks(rng: &mut Rng, size: usize) -> String {
+    const STACKS: usize = 9;
+
+    let mut stacks: Vec<Vec<char>> = (0..STACKS)
+        .map(|_| {
+            (0..rng.below(8))
+                .map(|_| char::from(b'A' + rng.below(26) as u8))
+                .collect()
+        })
+        .collect();
+
+    if stacks.iter().all(Vec::is_empty) {
+        stacks[0].push('A');
+    }
+
+    let mut input = String::new();
+
+    let height = stacks.iter().map(Vec::len).max().unwrap_or_default();
+    for level in (0..height).rev() {
+        let row: Vec<String> = stacks
+            .iter()
+            .map(|stack| stack.get(level).map_or("   ".into(), |c| format!("[{c}]")))
+            .collect();
+        input.push_str(row.join(" ").trim_end());
+        input.push('\n');
+    }
+
+    let numbers: Vec<String> = (1..=STACKS).map(|n| format!(" {n} ")).collect();
+    input.push_str(numbers.join(" ").trim_end());
+    input.push_str("\n\n");
+
+    for _ in 0..size {
+        let full: Vec<usize> = (0..STACKS).filter(|&i| !stacks[i].is_empty()).collect();
+        let from = rng.choose(&full).copied().unwrap_or_default();
+        let to = (from + rng.between(1, STACKS - 1)) % STACKS;
+        let quantity = rng.between(1, stacks[from].len());
+
+        // Stack heights come out the same whichever crane does the moving
+        let at = stacks[from].len() - quantity;
+        let moved = stacks[from].split_off(at);
+        stacks[to].extend(moved.into_iter().rev());
+
+        input.push_str(&format!(
+            "move {quantity} from {} to {}\n",
+            from + 1,
+            to + 1
+        ));
+    }
+
+    input
+}
+
+/// Day 6: a datastream of `size` random characters, followed by a start-of-message marker so there
+/// is always one to find. The random characters only use 13 letters, so the start-of-message
+/// marker is not found until the end and large inputs make for a long search.
+fn datastream(rng: &mut Rng, size: usize) -> String {
+    let mut marker: Vec<char> = ('a'..='z').collect();
+    rng.shuffle(&mut marker);
+
+    let mut datastream: String = (0..size).map(|_| marker[rng.below(13)]).collect();
+    datastream.extend(&marker[..14]);
+    datastream.push('\n');
+
+    datastream
+}
+
+/// A directory of a made-up filesystem.
+#[derive(Default)]
+struct Directory {
+    name: String,
+    files: Vec<(String, usize)>,
+    directories: Vec<usize>,
+}
+
+/// Day 7: a terminal session exploring a filesystem of around `size` files, which fits on the
+/// disk.
+fn terminal_output(rng: &mut Rng, size: usize) -> String {
+    // The disk holds 70000000, and some room is kept back for the files that fill empty
+    // directories. The update always fits by deleting the root directory.
+    const DISK_SPACE: usize = 69_000_000;
+
+    let mut directories = vec![Directory::default()];
+    let mut used = 0;
+
+    for i in 0..size {
+        let parent = rng.below(directories.len());
+
+        if rng.one_in(3) {
+            directories.push(Directory {
+                name: format!("{}{i}", name(rng)),
+                ..Directory::default()
+            });
+            let child = directories.len() - 1;
+            directories[parent].directories.push(child);
+        } else if used < DISK_SPACE {
+            let file_size = rng.between(1, 300_000).min(DISK_SPACE - used);
+            used += file_size;
+
+            let file = format!("{}{i}.{}", name(rng), name(rng));
+            directories[parent].files.push((file, file_size));
+        }
+    }
+
+    // Real filesystems have no empty directories
+    for (i, directory) in directories.iter_mut().enumerate() {
+        if directory.files.is_empty() {
+            let file_size = rng.between(1, 300_000).min(DISK_SPACE - used).max(1);
+            used += file_size;
+
+            directory
+                .files
+                .push((format!("{}{i}", name(rng)), file_size));
+        }
+    }
+
+    let mut input = String::from("$ cd /\n");
+    list(&directories, 0, &mut input);
+
+    input
+}
+
+/// A short, made-up file or directory name.
+fn name(rng: &mut Rng) -> String {
+    (0..rng.between(1, 6))
+        .map(|_| char::from(b'a' + rng.below(26) as u8))
+        .collect()
+}
+
+/// Lists directory `i` and then visits each directory inside it.
+fn list(directories: &[Directory], i: usize, input: &mut String) {
+    let directory = &directories[i];
+
+    input.push_str("$ ls\n");
+    for &child in &directory.directories {
+        input.push_str(&format!("dir {}\n", directories[child].name));
+    }
+    for (file, size) in &directory.files {
+        input.push_str(&format!("{size} {file}\n"));
+    }
+
+    for &child in &directory.directories {
+        input.push_str(&format!("$ cd {}\n", directories[child].name));
+        list(directories, child, input);
+        input.push_str("$ cd ..\n");
+    }
+}
+
+/// Day 8: a square forest `size` trees across.
+fn forest(rng: &mut Rng, size: usize) -> String {
+    (0..size)
+        .map(|_| {
+            let mut row: String = (0..size)
+                .map(|_| char::from(b'0' + rng.below(10) as u8))
+                .collect();
+            row.push('\n');
+            row
+        })
+        .collect()
+}
+
+/// Day 9: `size` motions of the head of the rope.
+fn rope_motions(rng: &mut Rng, size: usize) -> String {
+    (0..size)
+        .map(|_| {
+            let direction = rng.choose(&['U', 'D', 'L', 'R']).copied().unwrap_or('U');
+            format!("{direction} {}\n", rng.between(1, 19))
+        })
+        .collect()
+}
+
+/// Day 10: a program of `size` instructions.
+fn program(rng: &mut Rng, size: usize) -> String {
+    (0..size)
+        .map(|_| {
+            if rng.one_in(3) {
+                "noop\n".to_string()
+            } else {
+                let x = rng.between(1, 40) as i64 - 20;
+                format!("addx {x}\n")
+            }
+        })
+        .collect()
+}
+
+/// Day 11: notes on four monkeys holding `size` items between them, or one each if more, with the
+/// operations of the example's monkeys. Starting worry levels are kept below 10 so that items can
+/// go round the monkey that squares them a few times, but often enough they still overflow part 1,
+/// which the solution has to report.
+fn monkey_notes(rng: &mut Rng, size: usize) -> String {
+    const OPERATIONS: [&str; 4] = ["old * 19", "old + 6", "old * old", "old + 3"];
+
+    let mut divisors = [2, 3, 5, 7, 11, 13, 17, 19, 23];
+    rng.shuffle(&mut divisors);
+
+    // Every monkey starts with at least one item
+    let mut items: Vec<Vec<String>> = (0..OPERATIONS.len())
+        .map(|_| vec![rng.between(1, 9).to_string()])
+        .collect();
+    for _ in OPERATIONS.len()..size {
+        let monkey = rng.below(items.len());
+        items[monkey].push(rng.between(1, 9).to_string());
+    }
+
+    let notes: Vec<String> = OPERATIONS
+        .iter()
+        .enumerate()
+        .map(|(m, operation)| {
+            let mut others: Vec<usize> = (0..OPERATIONS.len())
+                .filter(|&n| n != m)
+                .collect();
+            rng.shuffle(&mut others);
+
+            format!(
+                "Monkey {m}:\n  Starting items: {}\n  Operation: new = {operation}\n  Test: divisible by {}\n    If true: throw to monkey {}\n    If false: throw to monkey {}\n",
+                items[m].join(", "),
+                divisors[m],
+                others[0],
+                others[1],
+            )
+        })
+        .collect();
+
+    notes.join("\n")
+}
+
+/// Day 12: a heightmap `size` squares across (at least enough to climb from `a` to `z`) and half
+/// as tall. Elevations rise from west to east, and a path with no steep steps runs from `S` on
+/// the west edge to `E` on the east edge.
+fn heightmap(rng: &mut Rng, size: usize) -> String {
+    let cols = size.max(26);
+    let rows = (size / 2).max(3);
+    let base = |col: usize| col * 25 / (cols - 1);
+
+    let mut map: Vec<Vec<u8>> = (0..rows)
+        .map(|_| {
+            (0..cols)
+                .map(|col| (base(col) + rng.below(5)).saturating_sub(2).min(25) as u8)
+                .collect()
+        })
+        .collect();
+
+    // Wander east, sometimes north or south, keeping to the base elevation of each column
+    let mut row = rng.below(rows);
+    let start = row;
+    let mut path = vec![];
+
+    for col in 0..cols {
+        path.push((row, col));
+
+        for _ in 0..rng.below(3) {
+            row = match rng.one_in(2) {
+                true => row.saturating_sub(1),
+                false => (row + 1).min(rows - 1),
+            };
+            path.push((row, col));
+        }
+    }
+
+    for (row, col) in path {
+        map[row][col] = base(col) as u8;
+    }
+
+    let mut map: Vec<Vec<char>> = map
+        .into_iter()
+        .map(|row| row.into_iter().map(|e| char::from(b'a' + e)).collect())
+        .collect();
+    map[start][0] = 'S';
+    map[row][cols - 1] = 'E';
+
+    map.into_iter()
+        .map(|row| row.into_iter().chain(['\n']).collect::<String>())
+        .collect()
+}
+
+/// Checks that `solution` gives the same answers as a deliberately naive `reference` solution on
+/// inputs made up for `day`, from tiny ones up. A reference that gives `None` has no answers for
+/// the input, and the solution has to fail on it too.
+#[cfg(test)]
+pub(crate) fn assert_matches_reference<R: Into<Option<crate::Answers>>>(
+    day: u8,
+    solution: crate::runner::Solution,
+    reference: fn(&str) -> R,
+) {
+    let mut rng = Rng::new(2022);
+
+    for size in [1, 2, 3, 5, 10, 30, 100] {
+        for _ in 0..5 {
+            let input = input(day, &mut rng, size).unwrap();
+            match (solution(input.clone()), reference(&input).into()) {
+                (Ok(answers), Some(expected)) => assert_eq!(
+                    answers, expected,
+                    "day {day} disagrees with its reference on\n{input}"
+                ),
+                (Err(_), None) => {}
+                (answers, _) => {
+                    panic!("day {day} gives {answers:?} where its reference does not on\n{input}")
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use super::*;
+    use crate::solution_for;
+
+    #[test]
+    fn inputs_are_solved() {
+        let mut rng = Rng::new(2022);
+
+        for day in 1..=12 {
+            for size in [1, 10, 100] {
+                let input = input(day, &mut rng, size).unwrap();
+                let solution = solution_for(day).unwrap();
+
+                match solution(input.clone()) {
+                    // Monkeys that square worry levels can make them overflow
+                    Err(e) if day == 11 && e.to_string().contains("overflowed") => {}
+                    Err(e) => panic!("day {day} failed on generated input: {e}\n{input}"),
+                    Ok(_) => {}
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn same_seed_same_input() {
+        for day in 1..=12 {
+            let first = input(day, &mut Rng::new(7), 50).unwrap();
+            let second = input(day, &mut Rng::new(7), 50).unwrap();
+            assert_eq!(first, second);
+        }
+
+        assert!(input(13, &mut Rng::new(7), 50).is_err());
+    }
+
+    #[test]
+    fn rucksacks_share_one_item() {
+        let input = rucksacks(&mut Rng::new(2022), 30);
+        let lines: Vec<_> = input.lines().collect();
+        assert_eq!(lines.len(), 30);
+
+        for line in &lines {
+            let (first, second) = line.split_at(line.len() / 2);
+            let shared: HashSet<char> = first.chars().filter(|&c| second.contains(c)).collect();
+            assert_eq!(shared.len(), 1, "{line}");
+        }
+
+        for group in lines.chunks(3) {
+            let badges: HashSet<char> = group[0]
+                .chars()
+                .filter(|&c| group[1].contains(c) && group[2].contains(c))
+                .collect();
+            assert_eq!(badges.len(), 1, "{group:?}");
+        }
+    }
+
+    #[test]
+    fn heightmap_can_be_climbed() {
+        let input = heightmap(&mut Rng::new(2022), 40);
+        let map: Vec<Vec<u8>> = input.lines().map(|line| line.bytes().collect()).collect();
+        let elevation = |b: u8| match b {
+            b'S' => b'a',
+            b'E' => b'z',
+            b => b,
+        };
+
+        let start = map
+            .iter()
+            .enumerate()
+            .find_map(|(r, row)| row.iter().position(|&b| b == b'S').map(|c| (r, c)))
+            .unwrap();
+
+        // Flood fill from S without climbing more than one step at a time
+        let mut seen = HashSet::from([start]);
+        let mut queue = vec![start];
+        let mut found = false;
+
+        while let Some((r, c)) = queue.pop() {
+            found |= map[r][c] == b'E';
+
+            let neighbours = [
+                (r.wrapping_sub(1), c),
+                (r + 1, c),
+                (r, c.wrapping_sub(1)),
+                (r, c + 1),
+            ];
+            for (nr, nc) in neighbours {
+                let Some(&next) = map.get(nr).and_then(|row| row.get(nc)) else {
+                    continue;
+                };
+                if elevation(next) <= elevation(map[r][c]) + 1 && seen.insert((nr, nc)) {
+                    queue.push((nr, nc));
+                }
+            }
+        }
+
+        assert!(found, "{input}");
+    }
+}
@@ -0,0 +1,346 @@
+use std::{
+    fmt,
+    ops::{Add, AddAssign, Mul, Neg, Sub},
+    str::FromStr,
+};
+
+use anyhow::bail;
+
+/// A point on a plane, with `y` growing upwards.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Point {
+    pub x: i64,
+    pub y: i64,
+}
+
+impl Point {
+    /// The point everything starts from.
+    pub const ORIGIN: Point = Point { x: 0, y: 0 };
+
+    pub fn new(x: i64, y: i64) -> Point {
+        Point { x, y }
+    }
+
+    /// The distance to `other` moving only along the axes, like a taxi around city blocks.
+    pub fn manhattan(self, other: Point) -> u64 {
+        (other - self).manhattan()
+    }
+
+    /// The distance to `other` moving along the axes and diagonals, like a king on a chessboard.
+    pub fn chebyshev(self, other: Point) -> u64 {
+        (other - self).chebyshev()
+    }
+
+    /// Rotates the point a quarter turn anticlockwise about `centre`.
+    pub fn rotate_left_about(self, centre: Point) -> Point {
+        centre + (self - centre).rotate_left()
+    }
+
+    /// Rotates the point a quarter turn clockwise about `centre`.
+    pub fn rotate_right_about(self, centre: Point) -> Point {
+        centre + (self - centre).rotate_right()
+    }
+}
+
+impl fmt::Display for Point {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "({}, {})", self.x, self.y)
+    }
+}
+
+/// A movement from one point to another.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct Vector {
+    pub x: i64,
+    pub y: i64,
+}
+
+impl Vector {
+    /// No movement at all.
+    pub const ZERO: Vector = Vector { x: 0, y: 0 };
+
+    pub fn new(x: i64, y: i64) -> Vector {
+        Vector { x, y }
+    }
+
+    /// The length of the vector moving only along the axes.
+    pub fn manhattan(self) -> u64 {
+        self.x.unsigned_abs() + self.y.unsigned_abs()
+    }
+
+    /// The length of the vector moving along the axes and diagonals.
+    pub fn chebyshev(self) -> u64 {
+        self.x.unsigned_abs().max(self.y.unsigned_abs())
+    }
+
+    /// A step of at most one along each axis in the same direction as the vector.
+    pub fn signum(self) -> Vector {
+        Vector::new(self.x.signum(), self.y.signum())
+    }
+
+    /// Rotates the vector a quarter turn anticlockwise.
+    pub fn rotate_left(self) -> Vector {
+        Vector::new(-self.y, self.x)
+    }
+
+    /// Rotates the vector a quarter turn clockwise.
+    pub fn rotate_right(self) -> Vector {
+        Vector::new(self.y, -self.x)
+    }
+}
+
+impl Add<Vector> for Point {
+    type Output = Point;
+
+    fn add(self, vector: Vector) -> Point {
+        Point::new(self.x + vector.x, self.y + vector.y)
+    }
+}
+
+impl AddAssign<Vector> for Point {
+    fn add_assign(&mut self, vector: Vector) {
+        *self = *self + vector;
+    }
+}
+
+impl Sub for Point {
+    type Output = Vector;
+
+    fn sub(self, other: Point) -> Vector {
+        Vector::new(self.x - other.x, self.y - other.y)
+    }
+}
+
+impl Add for Vector {
+    type Output = Vector;
+
+    fn add(self, other: Vector) -> Vector {
+        Vector::new(self.x + other.x, self.y + other.y)
+    }
+}
+
+impl Mul<i64> for Vector {
+    type Output = Vector;
+
+    fn mul(self, times: i64) -> Vector {
+        Vector::new(self.x * times, self.y * times)
+    }
+}
+
+impl Neg for Vector {
+    type Output = Vector;
+
+    fn neg(self) -> Vector {
+        Vector::new(-self.x, -self.y)
+    }
+}
+
+/// One of the four ways to move along the axes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Direction {
+    Up,
+    Down,
+    Left,
+    Right,
+}
+
+impl Direction {
+    /// Every direction, in the order up, down, left and right.
+    pub const ALL: [Direction; 4] = [
+        Direction::Up,
+        Direction::Down,
+        Direction::Left,
+        Direction::Right,
+    ];
+
+    /// A step of one in this direction on a plane, where up is towards larger `y`.
+    pub fn vector(self) -> Vector {
+        match self {
+            Direction::Up => Vector::new(0, 1),
+            Direction::Down => Vector::new(0, -1),
+            Direction::Left => Vector::new(-1, 0),
+            Direction::Right => Vector::new(1, 0),
+        }
+    }
+
+    /// A step of one in this direction on a grid, as rows and columns. Rows count downwards, so up
+    /// is towards smaller rows.
+    pub fn offset(self) -> (isize, isize) {
+        match self {
+            Direction::Up => (-1, 0),
+            Direction::Down => (1, 0),
+            Direction::Left => (0, -1),
+            Direction::Right => (0, 1),
+        }
+    }
+
+    /// The direction a quarter turn anticlockwise.
+    pub fn turn_left(self) -> Direction {
+        match self {
+            Direction::Up => Direction::Left,
+            Direction::Left => Direction::Down,
+            Direction::Down => Direction::Right,
+            Direction::Right => Direction::Up,
+        }
+    }
+
+    /// The direction a quarter turn clockwise.
+    pub fn turn_right(self) -> Direction {
+        self.turn_left().opposite()
+    }
+
+    /// The direction the other way.
+    pub fn opposite(self) -> Direction {
+        match self {
+            Direction::Up => Direction::Down,
+            Direction::Down => Direction::Up,
+            Direction::Left => Direction::Right,
+            Direction::Right => Direction::Left,
+        }
+    }
+}
+
+impl FromStr for Direction {
+    type Err = anyhow::Error;
+
+    /// Parses a direction from U/D/L/R or the compass points N/S/W/E.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let direction = match s {
+            "U" | "N" => Direction::Up,
+            "D" | "S" => Direction::Down,
+            "L" | "W" => Direction::Left,
+            "R" | "E" => Direction::Right,
+            s => bail!("{s} is not a direction"),
+        };
+
+        Ok(direction)
+    }
+}
+
+impl fmt::Display for Direction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let letter = match self {
+            Direction::Up => "U",
+            Direction::Down => "D",
+            Direction::Left => "L",
+            Direction::Right => "R",
+        };
+        write!(f, "{letter}")
+    }
+}
+
+/// The smallest rectangle, edges included, holding a set of points.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BoundingBox {
+    /// The bottom left corner.
+    pub min: Point,
+    /// The top right corner.
+    pub max: Point,
+}
+
+impl BoundingBox {
+    /// A box holding just `point`.
+    pub fn new(point: Point) -> BoundingBox {
+        BoundingBox {
+            min: point,
+            max: point,
+        }
+    }
+
+    /// The box around `points`, if there are any.
+    pub fn around(points: impl IntoIterator<Item = Point>) -> Option<BoundingBox> {
+        let mut points = points.into_iter();
+        let first = points.next()?;
+
+        Some(points.fold(BoundingBox::new(first), BoundingBox::including))
+    }
+
+    /// Grows the box to hold `point` too.
+    pub fn including(self, point: Point) -> BoundingBox {
+        BoundingBox {
+            min: Point::new(self.min.x.min(point.x), self.min.y.min(point.y)),
+            max: Point::new(self.max.x.max(point.x), self.max.y.max(point.y)),
+        }
+    }
+
+    /// Is `point` inside the box or on its edge?
+    pub fn contains(&self, point: Point) -> bool {
+        (self.min.x..=self.max.x).contains(&point.x) && (self.min.y..=self.max.y).contains(&point.y)
+    }
+
+    /// The number of columns of points in the box.
+    pub fn width(&self) -> u64 {
+        self.max.x.abs_diff(self.min.x) + 1
+    }
+
+    /// The number of rows of points in the box.
+    pub fn height(&self) -> u64 {
+        self.max.y.abs_diff(self.min.y) + 1
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn distances() {
+        let (a, b) = (Point::new(1, -2), Point::new(-3, 1));
+
+        assert_eq!(a.manhattan(b), 7);
+        assert_eq!(a.chebyshev(b), 4);
+        assert_eq!(b - a, Vector::new(-4, 3));
+        assert_eq!((b - a).signum(), Vector::new(-1, 1));
+        assert_eq!(a + (b - a) * 2, Point::new(-7, 4));
+    }
+
+    #[test]
+    fn rotation() {
+        let v = Vector::new(2, 1);
+
+        assert_eq!(v.rotate_left(), Vector::new(-1, 2));
+        assert_eq!(v.rotate_left().rotate_right(), v);
+        assert_eq!(
+            Point::new(3, 1).rotate_right_about(Point::new(1, 1)),
+            Point::new(1, -1)
+        );
+        assert_eq!(
+            Point::new(3, 1).rotate_left_about(Point::new(1, 1)),
+            Point::new(1, 3)
+        );
+
+        for direction in Direction::ALL {
+            assert_eq!(
+                direction.turn_left().vector(),
+                direction.vector().rotate_left()
+            );
+            assert_eq!(
+                direction.turn_right().vector(),
+                direction.vector().rotate_right()
+            );
+            assert_eq!(direction.opposite().vector(), -direction.vector());
+        }
+    }
+
+    #[test]
+    fn parse_directions() {
+        assert_eq!("U".parse::<Direction>().unwrap(), Direction::Up);
+        assert_eq!("W".parse::<Direction>().unwrap(), Direction::Left);
+        assert_eq!("E".parse::<Direction>().unwrap(), Direction::Right);
+        assert!("X".parse::<Direction>().is_err());
+        assert_eq!(Direction::Down.to_string(), "D");
+    }
+
+    #[test]
+    fn bounding_box() {
+        let points = [Point::new(1, 2), Point::new(-1, 0), Point::new(3, -4)];
+        let bounds = BoundingBox::around(points).unwrap();
+
+        assert_eq!(bounds.min, Point::new(-1, -4));
+        assert_eq!(bounds.max, Point::new(3, 2));
+        assert_eq!((bounds.width(), bounds.height()), (5, 7));
+        assert!(bounds.contains(Point::ORIGIN));
+        assert!(!bounds.contains(Point::new(4, 0)));
+        assert_eq!(BoundingBox::around([]), None);
+    }
+}
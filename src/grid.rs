@@ -0,0 +1,319 @@
+use std::{
+    fmt,
+    ops::{Index, IndexMut},
+};
+
+use anyhow::bail;
+
+use crate::geometry::Direction;
+
+/// A position on a grid, as a row and a column counting from the top left.
+pub type Position = (usize, usize);
+
+/// The steps to the eight neighbours of a square, clockwise from the top left.
+const SURROUNDING: [(isize, isize); 8] = [
+    (-1, -1),
+    (-1, 0),
+    (-1, 1),
+    (0, 1),
+    (1, 1),
+    (1, 0),
+    (1, -1),
+    (0, -1),
+];
+
+/// A rectangle of cells, like a map from a puzzle.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Grid<T> {
+    width: usize,
+    height: usize,
+    /// The cells row by row.
+    cells: Vec<T>,
+}
+
+impl<T> Grid<T> {
+    /// Creates a grid `width` cells wide and `height` cells high, filled with `cell`.
+    pub fn new(width: usize, height: usize, cell: T) -> Grid<T>
+    where
+        T: Clone,
+    {
+        Grid {
+            width,
+            height,
+            cells: vec![cell; width * height],
+        }
+    }
+
+    /// Creates a grid from its rows. Fails if the rows are not all the same length.
+    pub fn from_rows(rows: Vec<Vec<T>>) -> anyhow::Result<Grid<T>> {
+        let width = rows.first().map_or(0, Vec::len);
+        if rows.iter().any(|row| row.len() != width) {
+            bail!("the grid is not rectangular");
+        }
+
+        Ok(Grid {
+            width,
+            height: rows.len(),
+            cells: rows.into_iter().flatten().collect(),
+        })
+    }
+
+    /// Parses a map of characters, one row per line, turning each character into a cell with
+    /// `cell`. Fails if `cell` does or the rows are not all the same length.
+    pub fn parse(
+        input: &str,
+        mut cell: impl FnMut(char) -> anyhow::Result<T>,
+    ) -> anyhow::Result<Grid<T>> {
+        let rows = input
+            .lines()
+            .map(|line| line.chars().map(&mut cell).collect())
+            .collect::<anyhow::Result<_>>()?;
+
+        Grid::from_rows(rows)
+    }
+
+    /// The number of columns.
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    /// The number of rows.
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// Is `position` on the grid?
+    pub fn contains(&self, (row, col): Position) -> bool {
+        row < self.height && col < self.width
+    }
+
+    /// Gets the cell at `position`, if it is on the grid.
+    pub fn get(&self, position: Position) -> Option<&T> {
+        self.index_of(position).map(|i| &self.cells[i])
+    }
+
+    /// Gets the cell at `position` to change it, if it is on the grid.
+    pub fn get_mut(&mut self, position: Position) -> Option<&mut T> {
+        self.index_of(position).map(|i| &mut self.cells[i])
+    }
+
+    /// Where the cell at `position` is kept, if it is on the grid.
+    fn index_of(&self, position: Position) -> Option<usize> {
+        self.contains(position)
+            .then_some(position.0 * self.width + position.1)
+    }
+
+    /// Takes a step of `(rows, cols)` from `position`, if it stays on the grid.
+    pub fn step(&self, (row, col): Position, (rows, cols): (isize, isize)) -> Option<Position> {
+        let position = (row.checked_add_signed(rows)?, col.checked_add_signed(cols)?);
+        self.contains(position).then_some(position)
+    }
+
+    /// The rows, top first.
+    pub fn rows(&self) -> impl DoubleEndedIterator<Item = &[T]> {
+        (0..self.height).map(|row| &self.cells[row * self.width..(row + 1) * self.width])
+    }
+
+    /// Gets a row, if it is on the grid.
+    pub fn row(&self, row: usize) -> Option<&[T]> {
+        (row < self.height).then(|| &self.cells[row * self.width..(row + 1) * self.width])
+    }
+
+    /// The cells of a column, top first. Empty if the column is not on the grid.
+    pub fn column(&self, col: usize) -> impl DoubleEndedIterator<Item = &T> {
+        let height = if col < self.width { self.height } else { 0 };
+        (0..height).map(move |row| &self[(row, col)])
+    }
+
+    /// Every position on the grid, row by row.
+    pub fn positions(&self) -> impl Iterator<Item = Position> {
+        let width = self.width;
+        (0..self.height).flat_map(move |row| (0..width).map(move |col| (row, col)))
+    }
+
+    /// Every cell with its position, row by row.
+    pub fn iter(&self) -> impl Iterator<Item = (Position, &T)> {
+        self.positions().zip(&self.cells)
+    }
+
+    /// The position next to `position` in `direction`, if it is on the grid.
+    pub fn neighbour(&self, position: Position, direction: Direction) -> Option<Position> {
+        self.step(position, direction.offset())
+    }
+
+    /// The positions up, down, left and right of `position` that are on the grid.
+    pub fn neighbours(&self, position: Position) -> impl Iterator<Item = Position> + '_ {
+        Direction::ALL
+            .into_iter()
+            .filter_map(move |direction| self.neighbour(position, direction))
+    }
+
+    /// The positions of the eight squares around `position`, diagonals included, that are on the
+    /// grid.
+    pub fn surrounding(&self, position: Position) -> impl Iterator<Item = Position> + '_ {
+        SURROUNDING
+            .into_iter()
+            .filter_map(move |step| self.step(position, step))
+    }
+
+    /// The positions from `position` out to the edge of the grid, taking steps of `(rows, cols)`.
+    /// The starting position is left out.
+    pub fn ray(
+        &self,
+        position: Position,
+        step: (isize, isize),
+    ) -> impl Iterator<Item = Position> + '_ {
+        let mut position = Some(position);
+        std::iter::from_fn(move || {
+            position = position.and_then(|position| self.step(position, step));
+            position
+        })
+        // A step of nothing would never reach the edge
+        .take(if step == (0, 0) { 0 } else { usize::MAX })
+    }
+
+    /// The rays from `position` up, down, left and right, in that order.
+    pub fn rays(
+        &self,
+        position: Position,
+    ) -> impl Iterator<Item = impl Iterator<Item = Position> + '_> + '_ {
+        Direction::ALL
+            .into_iter()
+            .map(move |direction| self.ray(position, direction.offset()))
+    }
+
+    /// Flips the grid over its diagonal, so rows become columns.
+    pub fn transpose(&self) -> Grid<T>
+    where
+        T: Clone,
+    {
+        Grid {
+            width: self.height,
+            height: self.width,
+            cells: (0..self.width)
+                .flat_map(|col| self.column(col).cloned())
+                .collect(),
+        }
+    }
+
+    /// Creates a grid of the same size by turning each cell into another with `f`.
+    pub fn map<U>(&self, f: impl FnMut(&T) -> U) -> Grid<U> {
+        Grid {
+            width: self.width,
+            height: self.height,
+            cells: self.cells.iter().map(f).collect(),
+        }
+    }
+}
+
+impl<T> Index<Position> for Grid<T> {
+    type Output = T;
+
+    /// Gets the cell at `position`. Panics if it is not on the grid.
+    fn index(&self, position: Position) -> &T {
+        match self.get(position) {
+            Some(cell) => cell,
+            None => panic!(
+                "position {position:?} is outside a {}x{} grid",
+                self.width, self.height
+            ),
+        }
+    }
+}
+
+impl<T> IndexMut<Position> for Grid<T> {
+    /// Gets the cell at `position` to change it. Panics if it is not on the grid.
+    fn index_mut(&mut self, position: Position) -> &mut T {
+        let (width, height) = (self.width, self.height);
+        match self.get_mut(position) {
+            Some(cell) => cell,
+            None => panic!("position {position:?} is outside a {width}x{height} grid"),
+        }
+    }
+}
+
+impl<T: fmt::Display> fmt::Display for Grid<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for row in self.rows() {
+            for cell in row {
+                write!(f, "{cell}")?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn example() -> Grid<char> {
+        Grid::parse("abc\ndef\n", Ok).unwrap()
+    }
+
+    #[test]
+    fn parse_and_index() {
+        let grid = example();
+
+        assert_eq!((grid.width(), grid.height()), (3, 2));
+        assert_eq!(grid[(1, 2)], 'f');
+        assert_eq!(grid.get((2, 0)), None);
+        assert_eq!(grid.get((0, 3)), None);
+        assert_eq!(grid.to_string(), "abc\ndef\n");
+
+        assert!(Grid::parse("ab\nc", Ok).is_err());
+        assert!(Grid::parse("a?", |c| match c {
+            '?' => bail!("not a letter"),
+            c => Ok(c),
+        })
+        .is_err());
+    }
+
+    #[test]
+    fn rows_and_columns() {
+        let grid = example();
+
+        let rows: Vec<String> = grid.rows().map(|row| row.iter().collect()).collect();
+        assert_eq!(rows, ["abc", "def"]);
+        assert_eq!(grid.row(1), Some(&['d', 'e', 'f'][..]));
+        assert_eq!(grid.row(2), None);
+        assert_eq!(grid.column(1).collect::<String>(), "be");
+        assert_eq!(grid.column(3).count(), 0);
+        assert_eq!(grid.transpose().to_string(), "ad\nbe\ncf\n");
+        assert_eq!(grid.map(|c| c.to_ascii_uppercase())[(0, 0)], 'A');
+    }
+
+    #[test]
+    fn neighbours_and_rays() {
+        let grid = example();
+
+        assert_eq!(
+            grid.neighbours((0, 1)).collect::<Vec<_>>(),
+            [(1, 1), (0, 0), (0, 2)]
+        );
+        assert_eq!(grid.neighbour((0, 1), Direction::Up), None);
+        assert_eq!(grid.neighbour((0, 1), Direction::Right), Some((0, 2)));
+        assert_eq!(grid.surrounding((0, 0)).count(), 3);
+        assert_eq!(grid.surrounding((1, 1)).count(), 5);
+        assert_eq!(
+            grid.ray((1, 0), (0, 1)).collect::<Vec<_>>(),
+            [(1, 1), (1, 2)]
+        );
+        assert_eq!(grid.ray((1, 0), (0, 0)).count(), 0);
+        assert_eq!(
+            grid.rays((0, 0)).map(Iterator::count).collect::<Vec<_>>(),
+            [0, 1, 0, 2]
+        );
+    }
+
+    #[test]
+    fn empty_grid() {
+        let grid: Grid<char> = Grid::parse("", Ok).unwrap();
+
+        assert_eq!((grid.width(), grid.height()), (0, 0));
+        assert_eq!(grid.rows().count(), 0);
+        assert_eq!(grid.to_string(), "");
+        assert_eq!(Grid::new(0, 3, '.').to_string(), "\n\n\n");
+    }
+}
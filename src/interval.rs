@@ -0,0 +1,336 @@
+use std::fmt;
+
+/// A run of whole numbers with both ends included, like the sections `2-4`. It is never empty.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Interval {
+    start: i64,
+    end: i64,
+}
+
+impl Interval {
+    /// The numbers from `start` to `end`, or `None` if `end` comes before `start`.
+    pub fn new(start: i64, end: i64) -> Option<Interval> {
+        (start <= end).then_some(Interval { start, end })
+    }
+
+    /// Just the number `n`.
+    pub fn single(n: i64) -> Interval {
+        Interval { start: n, end: n }
+    }
+
+    pub fn start(&self) -> i64 {
+        self.start
+    }
+
+    pub fn end(&self) -> i64 {
+        self.end
+    }
+
+    /// How many numbers there are in the interval, which is one too many for a `u64` when it
+    /// holds every `i64`.
+    #[allow(clippy::len_without_is_empty)] // It is never empty
+    pub fn len(&self) -> u128 {
+        u128::from(self.end.abs_diff(self.start)) + 1
+    }
+
+    /// Is `n` in the interval?
+    pub fn contains(&self, n: i64) -> bool {
+        (self.start..=self.end).contains(&n)
+    }
+
+    /// Is every number in `other` in this interval too?
+    pub fn covers(&self, other: &Interval) -> bool {
+        self.start <= other.start && other.end <= self.end
+    }
+
+    /// Do the intervals have any numbers in common?
+    pub fn overlaps(&self, other: &Interval) -> bool {
+        self.start <= other.end && other.start <= self.end
+    }
+
+    /// The numbers the intervals have in common, if any.
+    pub fn intersection(&self, other: &Interval) -> Option<Interval> {
+        Interval::new(self.start.max(other.start), self.end.min(other.end))
+    }
+}
+
+impl fmt::Display for Interval {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}-{}", self.start, self.end)
+    }
+}
+
+/// A set of whole numbers, kept as the fewest intervals that hold them.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct IntervalSet {
+    /// In order, with gaps between them, so no two overlap or touch.
+    intervals: Vec<Interval>,
+}
+
+impl IntervalSet {
+    pub fn new() -> IntervalSet {
+        IntervalSet::default()
+    }
+
+    /// Sorts `intervals` and merges the ones that overlap or touch.
+    fn merged(mut intervals: Vec<Interval>) -> IntervalSet {
+        intervals.sort_unstable();
+
+        let mut merged: Vec<Interval> = Vec::with_capacity(intervals.len());
+        for interval in intervals {
+            match merged.last_mut() {
+                Some(last) if interval.start <= last.end.saturating_add(1) => {
+                    last.end = last.end.max(interval.end);
+                }
+                _ => merged.push(interval),
+            }
+        }
+
+        IntervalSet { intervals: merged }
+    }
+
+    /// Adds every number in `interval` to the set.
+    pub fn insert(&mut self, interval: Interval) {
+        let mut intervals = std::mem::take(&mut self.intervals);
+        intervals.push(interval);
+        *self = IntervalSet::merged(intervals);
+    }
+
+    /// The intervals holding the numbers in the set, in order.
+    pub fn intervals(&self) -> &[Interval] {
+        &self.intervals
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.intervals.is_empty()
+    }
+
+    /// How many numbers are in the set.
+    pub fn len(&self) -> u128 {
+        self.intervals.iter().map(Interval::len).sum()
+    }
+
+    /// Is `n` in the set?
+    pub fn contains(&self, n: i64) -> bool {
+        // The intervals are in order, so the only one that can hold `n` is the last to start by it
+        let i = self
+            .intervals
+            .partition_point(|interval| interval.start <= n);
+        i > 0 && self.intervals[i - 1].contains(n)
+    }
+
+    /// The interval from the smallest number in the set to the largest, if there are any.
+    pub fn span(&self) -> Option<Interval> {
+        let (first, last) = (self.intervals.first()?, self.intervals.last()?);
+        Interval::new(first.start, last.end)
+    }
+
+    /// The numbers in either set.
+    pub fn union(&self, other: &IntervalSet) -> IntervalSet {
+        IntervalSet::merged([&self.intervals[..], &other.intervals[..]].concat())
+    }
+
+    /// The numbers in both sets.
+    pub fn intersection(&self, other: &IntervalSet) -> IntervalSet {
+        let mut intervals = Vec::new();
+        let (mut i, mut j) = (0, 0);
+
+        while let (Some(a), Some(b)) = (self.intervals.get(i), other.intervals.get(j)) {
+            intervals.extend(a.intersection(b));
+
+            // Whichever ends first cannot overlap anything further on in the other set
+            if a.end < b.end {
+                i += 1;
+            } else {
+                j += 1;
+            }
+        }
+
+        IntervalSet { intervals }
+    }
+
+    /// The numbers in this set but not in `other`.
+    pub fn difference(&self, other: &IntervalSet) -> IntervalSet {
+        let mut intervals = Vec::new();
+        let mut j = 0;
+
+        for interval in &self.intervals {
+            let mut rest = Some(*interval);
+
+            // Skip what ends before this interval, then cut away what overlaps it
+            while other
+                .intervals
+                .get(j)
+                .is_some_and(|b| b.end < interval.start)
+            {
+                j += 1;
+            }
+            for b in other.intervals[j..]
+                .iter()
+                .take_while(|b| b.start <= interval.end)
+            {
+                let Some(current) = rest else { break };
+                if b.start > current.start {
+                    intervals.push(Interval {
+                        start: current.start,
+                        end: b.start - 1,
+                    });
+                }
+                rest = b
+                    .end
+                    .checked_add(1)
+                    .and_then(|start| Interval::new(start, current.end));
+            }
+
+            intervals.extend(rest);
+        }
+
+        IntervalSet { intervals }
+    }
+
+    /// The runs of numbers missing between the smallest number in the set and the largest.
+    pub fn gaps(&self) -> impl Iterator<Item = Interval> + '_ {
+        self.intervals.windows(2).map(|pair| Interval {
+            start: pair[0].end + 1,
+            end: pair[1].start - 1,
+        })
+    }
+}
+
+impl From<Interval> for IntervalSet {
+    fn from(interval: Interval) -> IntervalSet {
+        IntervalSet {
+            intervals: vec![interval],
+        }
+    }
+}
+
+impl FromIterator<Interval> for IntervalSet {
+    fn from_iter<I: IntoIterator<Item = Interval>>(intervals: I) -> IntervalSet {
+        IntervalSet::merged(intervals.into_iter().collect())
+    }
+}
+
+impl fmt::Display for IntervalSet {
+    /// Lists the intervals like `1-3, 7-7`.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, interval) in self.intervals.iter().enumerate() {
+            if i > 0 {
+                write!(f, ", ")?;
+            }
+            write!(f, "{interval}")?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn interval(start: i64, end: i64) -> Interval {
+        Interval::new(start, end).unwrap()
+    }
+
+    fn set(intervals: &[(i64, i64)]) -> IntervalSet {
+        intervals
+            .iter()
+            .map(|&(start, end)| interval(start, end))
+            .collect()
+    }
+
+    /// The numbers from -10 to 30 in `set`, to check the set operations against.
+    fn numbers(set: &IntervalSet) -> Vec<i64> {
+        (-10..=30).filter(|&n| set.contains(n)).collect()
+    }
+
+    #[test]
+    fn intervals() {
+        let (a, b) = (interval(2, 8), interval(3, 7));
+
+        assert_eq!(Interval::new(4, 3), None);
+        assert_eq!(a.len(), 7);
+        assert!(a.contains(8) && !a.contains(9));
+        assert!(a.covers(&b) && !b.covers(&a));
+        assert!(a.overlaps(&interval(8, 9)));
+        assert!(!a.overlaps(&interval(9, 9)));
+        assert_eq!(a.intersection(&interval(5, 12)), Some(interval(5, 8)));
+        assert_eq!(a.intersection(&interval(-3, 1)), None);
+        assert_eq!(interval(2, 4).to_string(), "2-4");
+    }
+
+    #[test]
+    fn merge_intervals() {
+        let set = set(&[(5, 7), (1, 2), (3, 4), (10, 12), (11, 20)]);
+
+        assert_eq!(set.intervals(), [interval(1, 7), interval(10, 20)]);
+        assert_eq!(set.len(), 18);
+        assert_eq!(set.span(), Some(interval(1, 20)));
+        assert_eq!(set.gaps().collect::<Vec<_>>(), [interval(8, 9)]);
+        assert_eq!(set.to_string(), "1-7, 10-20");
+        assert!(set.contains(10) && !set.contains(9) && !set.contains(0));
+
+        let mut inserted = IntervalSet::new();
+        assert!(inserted.is_empty());
+        inserted.insert(interval(i64::MAX - 1, i64::MAX));
+        inserted.insert(interval(i64::MAX, i64::MAX));
+        assert_eq!(inserted.intervals(), [interval(i64::MAX - 1, i64::MAX)]);
+    }
+
+    #[test]
+    fn extremes() {
+        let everything = interval(i64::MIN, i64::MAX);
+        assert_eq!(everything.len(), 1 << 64);
+        assert_eq!(IntervalSet::from(everything).len(), 1 << 64);
+        assert_eq!(interval(i64::MIN, i64::MIN).len(), 1);
+
+        let ends = set(&[(i64::MIN, -1), (1, i64::MAX)]);
+        assert_eq!(ends.len(), (1 << 64) - 1);
+        assert_eq!(ends.gaps().collect::<Vec<_>>(), [interval(0, 0)]);
+    }
+
+    #[test]
+    fn set_operations() {
+        let sets = [
+            set(&[]),
+            set(&[(0, 5)]),
+            set(&[(-5, -1), (2, 3), (8, 12), (20, 25)]),
+            set(&[(1, 2), (4, 9), (11, 11), (14, 30)]),
+            set(&[(-10, 30)]),
+        ];
+
+        for a in &sets {
+            for b in &sets {
+                let (a_numbers, b_numbers) = (numbers(a), numbers(b));
+
+                let union: Vec<i64> = (-10..=30)
+                    .filter(|n| a_numbers.contains(n) || b_numbers.contains(n))
+                    .collect();
+                let intersection: Vec<i64> = a_numbers
+                    .iter()
+                    .copied()
+                    .filter(|n| b_numbers.contains(n))
+                    .collect();
+                let difference: Vec<i64> = a_numbers
+                    .iter()
+                    .copied()
+                    .filter(|n| !b_numbers.contains(n))
+                    .collect();
+
+                assert_eq!(numbers(&a.union(b)), union, "{a} or {b}");
+                assert_eq!(numbers(&a.intersection(b)), intersection, "{a} and {b}");
+                assert_eq!(numbers(&a.difference(b)), difference, "{a} but not {b}");
+                assert_eq!(a.union(b), a.union(b).union(&set(&[])), "{a} or {b}");
+            }
+        }
+    }
+
+    #[test]
+    fn uncovered() {
+        let covered = set(&[(2, 4), (6, 8)]);
+        let everything = IntervalSet::from(interval(1, 9));
+
+        assert_eq!(everything.difference(&covered).to_string(), "1-1, 5-5, 9-9");
+        assert_eq!(covered.gaps().collect::<Vec<_>>(), [Interval::single(5)]);
+    }
+}
@@ -0,0 +1,172 @@
+use std::{fmt, str::FromStr};
+
+use anyhow::bail;
+
+/// A set of ASCII letters, kept as one bit per letter: `a` to `z` then `A` to `Z`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct LetterSet(u64);
+
+impl LetterSet {
+    pub fn new() -> LetterSet {
+        LetterSet::default()
+    }
+
+    /// Where `letter` comes in the order `a` to `z` then `A` to `Z`, counting from zero, or `None`
+    /// if it is not an ASCII letter.
+    pub fn index(letter: char) -> Option<u32> {
+        // Works out the index from the bits of the letter rather than which case it is in, since
+        // branching on a mix of cases is slow
+        letter.is_ascii_alphabetic().then(|| {
+            let code = letter as u32;
+            let uppercase = (code & 0x20) ^ 0x20;
+            (code & 0x1f) - 1 + 26 * (uppercase >> 5)
+        })
+    }
+
+    /// Adds `letter` to the set, returning whether it was new. Panics if it is not an ASCII letter.
+    pub fn insert(&mut self, letter: char) -> bool {
+        let Some(index) = LetterSet::index(letter) else {
+            panic!("{letter:?} is not an ASCII letter");
+        };
+
+        let new = self.0 & 1 << index == 0;
+        self.0 |= 1 << index;
+        new
+    }
+
+    /// Takes `letter` out of the set, returning whether it was there.
+    pub fn remove(&mut self, letter: char) -> bool {
+        let had = self.contains(letter);
+        if let Some(index) = LetterSet::index(letter) {
+            self.0 &= !(1 << index);
+        }
+        had
+    }
+
+    pub fn contains(&self, letter: char) -> bool {
+        LetterSet::index(letter).is_some_and(|index| self.0 & 1 << index != 0)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0 == 0
+    }
+
+    /// How many letters are in the set.
+    pub fn len(&self) -> usize {
+        self.0.count_ones() as usize
+    }
+
+    /// The letters in either set.
+    pub fn union(&self, other: &LetterSet) -> LetterSet {
+        LetterSet(self.0 | other.0)
+    }
+
+    /// The letters in both sets.
+    pub fn intersection(&self, other: &LetterSet) -> LetterSet {
+        LetterSet(self.0 & other.0)
+    }
+
+    /// The letters in the set, `a` to `z` then `A` to `Z`.
+    pub fn iter(&self) -> impl Iterator<Item = char> {
+        let mut bits = self.0;
+        std::iter::from_fn(move || {
+            if bits == 0 {
+                return None;
+            }
+            let index = bits.trailing_zeros();
+            bits &= bits - 1;
+
+            let letter = match index {
+                0..=25 => b'a' + index as u8,
+                _ => b'A' + (index - 26) as u8,
+            };
+            Some(char::from(letter))
+        })
+    }
+}
+
+impl FromStr for LetterSet {
+    type Err = anyhow::Error;
+
+    /// The letters in `s`, failing on anything that is not an ASCII letter.
+    fn from_str(s: &str) -> anyhow::Result<LetterSet> {
+        let mut set = LetterSet::new();
+        for c in s.chars() {
+            let Some(index) = LetterSet::index(c) else {
+                bail!("{c:?} is not a letter");
+            };
+            set.0 |= 1 << index;
+        }
+        Ok(set)
+    }
+}
+
+impl fmt::Display for LetterSet {
+    /// Writes the letters in order, like `acZ`.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.iter().try_for_each(|letter| write!(f, "{letter}"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeSet;
+
+    use super::*;
+
+    fn set(letters: &str) -> LetterSet {
+        letters.parse().unwrap()
+    }
+
+    #[test]
+    fn letters() {
+        let mut letters = LetterSet::new();
+        assert!(letters.is_empty());
+        assert!(letters.insert('Z') && letters.insert('a') && letters.insert('q'));
+        assert!(!letters.insert('a'));
+        assert_eq!(letters.len(), 3);
+        assert_eq!(letters.to_string(), "aqZ");
+
+        assert!(letters.remove('q') && !letters.remove('q') && !letters.remove('!'));
+        assert!(letters.contains('Z') && !letters.contains('z') && !letters.contains('\n'));
+        assert_eq!(letters.iter().collect::<String>(), "aZ");
+
+        assert_eq!(LetterSet::index('a'), Some(0));
+        assert_eq!(LetterSet::index('Z'), Some(51));
+        assert_eq!(LetterSet::index('@'), None);
+        assert!("ab1".parse::<LetterSet>().is_err());
+    }
+
+    #[test]
+    #[should_panic(expected = "is not an ASCII letter")]
+    fn insert_non_letter() {
+        LetterSet::new().insert('é');
+    }
+
+    #[test]
+    fn set_operations() {
+        let sets = [
+            "",
+            "abc",
+            "vJrwpWtwJgWr",
+            "hcsFMMfFFhFp",
+            "zZaA",
+            "abcdefghijklmnopqrstuvwxyz",
+        ];
+
+        for a in sets {
+            for b in sets {
+                let (a_letters, b_letters): (BTreeSet<char>, BTreeSet<char>) =
+                    (a.chars().collect(), b.chars().collect());
+                let letters = |set: LetterSet| set.iter().collect::<BTreeSet<char>>();
+
+                assert_eq!(letters(set(a).union(&set(b))), &a_letters | &b_letters);
+                assert_eq!(
+                    letters(set(a).intersection(&set(b))),
+                    &a_letters & &b_letters
+                );
+                assert_eq!(set(a).len(), a_letters.len(), "{a}");
+            }
+        }
+    }
+}
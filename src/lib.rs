@@ -1,27 +1,251 @@
 use std::{
-    fs::File,
-    io::{self, ErrorKind, Write},
-    path::Path,
-    time::Duration,
+    fmt,
+    fs::{self, File, Metadata},
+    io::{self, BufReader, ErrorKind, Write},
+    path::{Path, PathBuf},
+    process, slice,
+    time::{Duration, SystemTime},
 };
 
-use anyhow::bail;
-use clap::Parser;
-use reqwest::Url;
+use anyhow::{anyhow, bail};
+use clap::{Parser, Subcommand};
+
+use website::Website;
 
 /// CLI for running Advent of Code challenges.
 #[derive(Parser)]
 #[command(author, version, about, long_about = None)]
 pub struct Config {
+    /// The command to run. Runs the solution for `--day` if omitted.
+    #[command(subcommand)]
+    command: Option<Command>,
+
     /// The day of the solution to run.
-    #[arg(short, long)]
+    #[arg(short, long, global = true)]
     day: Option<u8>,
 
-    /// A session cookie to allow pulling input for the day from the website.
-    #[arg(short, long)]
+    /// A session cookie to allow pulling input for the day from the website. Defaults to the
+    /// AOC_SESSION environment variable.
+    #[arg(short, long, global = true)]
     session: Option<String>,
+
+    /// A file containing the session cookie, which keeps it out of shell history.
+    #[arg(long, global = true, conflicts_with = "session")]
+    session_file: Option<PathBuf>,
+
+    /// Downloads the input again even if it is already cached.
+    #[arg(long, global = true)]
+    refresh: bool,
+
+    /// How long a solution may run before it is killed, e.g. 30s. Solutions run in a child process
+    /// so that they can be killed, except while recording animations for `--export` or `report`,
+    /// where a solution that runs out of time is only left behind and carries on until the program
+    /// exits.
+    #[arg(long, global = true, value_parser = runner::parse_duration)]
+    timeout: Option<Duration>,
+
+    /// Runs solutions in a child process with resource limits, so runaway solutions cannot take
+    /// down the whole run.
+    #[arg(long, global = true)]
+    sandbox: bool,
+
+    /// The most memory a sandboxed solution may use, in MiB. 1024 by default with `--sandbox`.
+    #[arg(long, global = true, value_parser = clap::value_parser!(u64).range(..=sandbox::Limits::MAX_MEMORY))]
+    memory_limit: Option<u64>,
+
+    /// The most CPU time a sandboxed solution may use, in seconds.
+    #[arg(long, global = true, value_parser = clap::value_parser!(u64).range(..=sandbox::Limits::MAX_CPU))]
+    cpu_limit: Option<u64>,
+
+    /// Narrates what the solution does step by step, like the puzzle text. Best used with the
+    /// example input, since real inputs make for a very long story.
+    #[arg(long, global = true)]
+    explain: bool,
+
+    /// Plays an animation of the solution in the terminal, for days that can draw one.
+    #[arg(long, global = true)]
+    visualize: bool,
+
+    /// How many frames per second `--visualize` plays.
+    #[arg(long, global = true, default_value_t = 10, value_parser = clap::value_parser!(u32).range(1..))]
+    fps: u32,
+
+    /// Saves the animation of the solution for `--day` to an image: an animated .gif, or a .png
+    /// or .ppm of its last frame.
+    #[arg(long, global = true, conflicts_with = "sandbox")]
+    export: Option<PathBuf>,
+
+    /// The implementation of the solution to run, or `all` to run every one and check that they
+    /// agree. Runs the first one if omitted.
+    #[arg(long, global = true)]
+    variant: Option<String>,
+}
+
+/// The answers to the two parts of a day's challenge.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Answers {
+    /// The answer to part 1, if it has been solved.
+    pub part1: Option<String>,
+    /// The answer to part 2, if it has been solved.
+    pub part2: Option<String>,
+}
+
+impl Answers {
+    /// Creates the answers to both parts.
+    pub fn new(part1: impl ToString, part2: impl ToString) -> Answers {
+        Answers {
+            part1: Some(part1.to_string()),
+            part2: Some(part2.to_string()),
+        }
+    }
+
+    /// Creates the answers when only part 1 has been solved.
+    pub fn part1(part1: impl ToString) -> Answers {
+        Answers {
+            part1: Some(part1.to_string()),
+            part2: None,
+        }
+    }
+}
+
+impl fmt::Display for Answers {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (part, answer) in [(1, &self.part1), (2, &self.part2)] {
+            if part > 1 {
+                write!(f, ", ")?;
+            }
+
+            match answer {
+                // Pictures like day 10's CRT start on a line of their own
+                Some(answer) if answer.contains('\n') => write!(f, "part {part}:\n{answer}")?,
+                Some(answer) => write!(f, "part {part}: {answer}")?,
+                None => write!(f, "part {part}: unsolved")?,
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Commands other than running a solution.
+#[derive(Subcommand)]
+enum Command {
+    /// Checks the session cookie with the website and reports who it belongs to.
+    Whoami,
+    /// Downloads the input for `--day` into the input cache.
+    Fetch {
+        /// Downloads the input for every unlocked day instead.
+        #[arg(long)]
+        all: bool,
+
+        /// The number of seconds to wait between requests to the website.
+        #[arg(long, default_value_t = 5)]
+        interval: u64,
+    },
+    /// Runs the solution for every day, carrying on past failures.
+    RunAll,
+    /// Runs the solution for every day and writes an HTML page with the answers, timings and
+    /// animations of each.
+    Report {
+        /// The file to write the page to.
+        #[arg(long, default_value = "report.html")]
+        out: PathBuf,
+    },
+    /// Writes a made-up input for `--day` to stdout.
+    Gen {
+        /// The seed for making up the input. Picked from the clock if not given.
+        #[arg(long)]
+        seed: Option<u64>,
+
+        /// Roughly how big the input is, e.g. the number of lines.
+        #[arg(long, default_value_t = 100)]
+        size: usize,
+    },
+    /// Feeds mutated example inputs to the input parsers, reporting inputs that make them panic.
+    Fuzz {
+        /// The parser to fuzz, e.g. day5-instruction. Fuzzes every parser if not given.
+        #[arg(long)]
+        target: Option<String>,
+
+        /// The number of inputs to try on each parser.
+        #[arg(long, default_value_t = 100_000)]
+        iterations: usize,
+
+        /// The seed for making up inputs. Picked from the clock if not given.
+        #[arg(long)]
+        seed: Option<u64>,
+    },
+    /// Runs the solution for `--day` on input from stdin, as a sandboxed child process.
+    #[command(hide = true)]
+    Solve,
+}
+
+impl Config {
+    /// Gets the session cookie from `--session`, `--session-file` or the AOC_SESSION environment
+    /// variable, in that order.
+    fn session(&self) -> anyhow::Result<Option<String>> {
+        match (&self.session, &self.session_file) {
+            (Some(session), _) => Ok(Some(session.clone())),
+            (None, Some(path)) => read_session_file(path).map(Some),
+            (None, None) => Ok(std::env::var("AOC_SESSION").ok()),
+        }
+    }
+
+    /// The resource limits for sandboxed solutions. Only `--sandbox` brings a memory limit of its
+    /// own, so a child process run to be killed on time, which is not passed one, gets none.
+    fn limits(&self) -> sandbox::Limits {
+        let default_memory = self.sandbox.then_some(sandbox::Limits::DEFAULT_MEMORY);
+        sandbox::Limits {
+            memory: self.memory_limit.or(default_memory),
+            cpu: self.cpu_limit,
+        }
+    }
+
+    /// Creates a client for the website if a session cookie was provided.
+    fn website(&self) -> anyhow::Result<Option<Website>> {
+        self.session()?
+            .map(|session| Website::new(Website::BASE_URL, session))
+            .transpose()
+    }
+}
+
+/// Reads a session cookie from the file at `path`, warning if other users can read it.
+fn read_session_file(path: &Path) -> anyhow::Result<String> {
+    let file = File::open(path)
+        .map_err(|e| anyhow!("failed to open session file {}: {e}", path.display()))?;
+
+    if is_world_readable(&file.metadata()?) {
+        log::warn!(
+            "session file {} is readable by other users, consider `chmod 600` on it",
+            path.display()
+        );
+    }
+
+    let session = io::read_to_string(file)?;
+    let session = session.trim();
+
+    if session.is_empty() {
+        bail!("session file {} is empty", path.display());
+    }
+
+    Ok(session.to_string())
+}
+
+/// Can users other than the owner read the file?
+#[cfg(unix)]
+fn is_world_readable(metadata: &Metadata) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+
+    metadata.permissions().mode() & 0o004 != 0
+}
+
+/// Can users other than the owner read the file?
+#[cfg(not(unix))]
+fn is_world_readable(_metadata: &Metadata) -> bool {
+    false
 }
 
+/// Finds where simulations start repeating, so they can skip ahead.
+pub mod cycle;
 /// Day 1 solution.
 pub mod day1;
 /// Day 10 solution.
@@ -48,6 +272,153 @@ pub mod day7;
 pub mod day8;
 /// Day 9 solution.
 pub mod day9;
+/// Narrates what solutions do, step by step.
+pub mod explain;
+/// Writes animation frames to image files.
+pub mod export;
+/// Feeds random input to the puzzle input parsers.
+pub mod fuzz;
+/// Makes up valid puzzle inputs.
+pub mod gen;
+/// Points, directions and distances on a plane.
+pub mod geometry;
+/// Rectangles of cells, like the maps in puzzles.
+pub mod grid;
+/// Runs of whole numbers and sets of them.
+pub mod interval;
+/// Sets of ASCII letters packed into a bitmask.
+pub mod letters;
+/// Number theory, like greatest common divisors and modular arithmetic.
+pub mod math;
+/// Reads the block letters that puzzles draw.
+pub mod ocr;
+/// Helpers for reading puzzle input: paragraphs, integers, keys and values, and line templates.
+pub mod parsing;
+/// Finds the shortest paths through graphs.
+pub mod pathfinding;
+/// Writes HTML reports of a full run.
+pub mod report;
+/// Seedable random numbers for making up inputs.
+pub mod rng;
+/// Runs solutions on their own thread with a time limit.
+pub mod runner;
+/// Runs solutions in a child process with resource limits.
+pub mod sandbox;
+/// Draws solutions as terminal animations.
+pub mod visualize;
+/// Client for the Advent of Code website.
+pub mod website;
+
+/// Runs the command from the given configuration, starting an async runtime for it. A sandboxed
+/// solution runs without one, since its resource limits must be in place before any runtime
+/// threads exist.
+pub fn start(config: Config) -> anyhow::Result<()> {
+    explain::enable(config.explain);
+    visualize::enable(config.visualize.then_some(config.fps));
+
+    if let Some(Command::Solve) = config.command {
+        let day = config.day.unwrap_or_default();
+        let [variant] = select_variants(day, config.variant.as_deref())? else {
+            bail!("the solve command runs exactly one variant");
+        };
+
+        return sandbox::solve(*variant, config.limits());
+    }
+
+    tokio::runtime::Runtime::new()?.block_on(run(config))
+}
+
+/// Runs the command from the given configuration.
+pub async fn run(config: Config) -> anyhow::Result<()> {
+    match config.command {
+        None => solution(config).await,
+        Some(Command::Whoami) => whoami(config).await,
+        Some(Command::Fetch { all, interval }) => fetch(config, all, interval).await,
+        Some(Command::RunAll) => run_all(config).await,
+        Some(Command::Report { ref out }) => report(&config, out).await,
+        Some(Command::Gen { seed, size }) => gen(config.day, seed, size),
+        Some(Command::Fuzz {
+            target,
+            iterations,
+            seed,
+        }) => fuzz(target.as_deref(), iterations, seed),
+        Some(Command::Solve) => bail!("the solve command cannot run inside an async runtime"),
+    }
+}
+
+/// Reports the user that the configured session cookie belongs to.
+async fn whoami(config: Config) -> anyhow::Result<()> {
+    let Some(website) = config.website()? else {
+        bail!("no session cookie provided to check with the website");
+    };
+
+    let user = website.whoami().await?;
+    log::info!("session cookie belongs to {user}");
+
+    Ok(())
+}
+
+/// Downloads inputs into the input cache.
+async fn fetch(config: Config, all: bool, interval: u64) -> anyhow::Result<()> {
+    let Some(website) = config.website()? else {
+        bail!("no session cookie provided to get input from website");
+    };
+
+    let dir = Path::new(INPUT_DIR);
+
+    if all {
+        let interval = Duration::from_secs(interval);
+        fetch_all(dir, &website, interval, config.refresh, SystemTime::now()).await?;
+    } else {
+        let day = config.day.unwrap_or(1);
+        input_file(day, dir, Some(&website), config.refresh).await?;
+        log::info!("input for day {day} is cached");
+    }
+
+    Ok(())
+}
+
+/// Downloads the input of every day unlocked by `now` into the cache in `dir`, waiting `interval`
+/// between requests so as not to hammer the website. Days that are already cached are skipped
+/// unless `refresh` is set. Returns the days that were downloaded.
+pub async fn fetch_all(
+    dir: &Path,
+    website: &Website,
+    interval: Duration,
+    refresh: bool,
+    now: SystemTime,
+) -> anyhow::Result<Vec<u8>> {
+    let mut fetched = Vec::new();
+    let mut failed = Vec::new();
+
+    for day in website::unlocked_days(now) {
+        if !refresh && dir.join(format!("day_{day}.txt")).exists() {
+            log::info!("input for day {day} is already cached");
+            continue;
+        }
+
+        if !(fetched.is_empty() && failed.is_empty()) {
+            tokio::time::sleep(interval).await;
+        }
+
+        match input_file(day, dir, Some(website), true).await {
+            Ok(_) => {
+                log::info!("fetched input for day {day}");
+                fetched.push(day);
+            }
+            Err(e) => {
+                log::error!("failed to fetch input for day {day}: {e}");
+                failed.push(day);
+            }
+        }
+    }
+
+    if !failed.is_empty() {
+        bail!("failed to fetch input for days {failed:?}");
+    }
+
+    Ok(fetched)
+}
 
 /// Runs a solution from the given configuration.
 pub async fn solution(config: Config) -> anyhow::Result<()> {
@@ -61,80 +432,527 @@ pub async fn solution(config: Config) -> anyhow::Result<()> {
         x => x,
     };
 
+    let variants = select_variants(day, config.variant.as_deref())?;
+
     log::info!("running solution for day {day}");
 
-    let input = match input(day, config.session).await {
-        Ok(s) => s,
+    let website = config.website()?;
+
+    let dir = Path::new(INPUT_DIR);
+    let input = match input_file(day, dir, website.as_ref(), config.refresh).await {
+        Ok(path) => path,
         Err(e) => bail!("failed to get input for day {day}: {e}"),
     };
 
-    match day {
-        1 => day1::main(input),
-        2 => day2::main(input),
-        3 => day3::main(input),
-        4 => day4::main(input),
-        5 => day5::main(input),
-        6 => day6::main(input),
-        7 => day7::main(input),
-        8 => day8::main(input),
-        9 => day9::main(input),
-        10 => day10::main(input),
-        11 => day11::main(input),
-        12 => day12::main(input),
-        13 => day13::main(input),
-        d => bail!("solution for day {d} does not exist"),
-    }
-}
-
-/// Gets the input for a challenge.
-async fn input(day: u8, session: Option<String>) -> anyhow::Result<String> {
-    let filepath = format!("input/day_{day}.txt");
-    let filepath = Path::new(&filepath);
-
-    let input = match File::open(filepath) {
-        Ok(f) => {
-            log::debug!("getting input from file {}", filepath.to_string_lossy());
-
-            io::read_to_string(f)?
-        }
-        Err(e) => {
-            let ErrorKind::NotFound = e.kind() else {
-                bail!(
-                    "failed to process input file {}: {e}",
-                    filepath.to_string_lossy()
-                );
-            };
+    if config.export.is_some() {
+        visualize::record();
+    }
+
+    let outcome = run_variants(&config, day, variants, &input).0;
+
+    if let Some(path) = &config.export {
+        let frames = visualize::recorded();
+        if frames.len() == visualize::MAX_RECORDED {
+            log::warn!("only the first {} frames were kept", frames.len());
+        }
+        export::save(path, &frames, config.fps)?;
+        log::info!("saved the animation of day {day} to {}", path.display());
+    }
+
+    match outcome {
+        runner::Outcome::Finished(result) => result.map(|_| ()),
+        outcome => bail!("solution for day {day} {outcome}"),
+    }
+}
+
+/// Runs the solution for every day, reporting how each one went.
+async fn run_all(config: Config) -> anyhow::Result<()> {
+    if config.export.is_some() {
+        bail!("--export saves one day at a time, so it cannot be used with run-all");
+    }
+
+    let failed: Vec<_> = run_days(&config, false)
+        .await?
+        .into_iter()
+        .filter(|entry| !entry.outcome.is_ok())
+        .map(|entry| entry.day)
+        .collect();
+
+    if !failed.is_empty() {
+        bail!("solutions failed for days {failed:?}");
+    }
+
+    Ok(())
+}
+
+/// Runs the solution for every day and writes how each one went to an HTML page at `out`.
+async fn report(config: &Config, out: &Path) -> anyhow::Result<()> {
+    if config.sandbox {
+        log::warn!("sandboxed solutions cannot be recorded, so the report has no animations");
+    }
 
-            let Some(session) = session else {
-                bail!(
-                    "failed to find input file {} and no session cookie provided to get input from website",
-                    filepath.to_string_lossy()
+    let entries = run_days(config, true).await?;
+    fs::write(out, report::html(&entries)?)?;
+    log::info!("wrote the report to {}", out.display());
+
+    Ok(())
+}
+
+/// Runs the solution for every day, carrying on past failures and logging how each one went.
+/// Records the animations of the solutions too if `record` is set.
+async fn run_days(config: &Config, record: bool) -> anyhow::Result<Vec<report::Entry>> {
+    let website = config.website()?;
+    let mut entries = Vec::new();
+
+    for day in 1..=website::DAYS {
+        if variants_for(day).is_empty() {
+            continue;
+        }
+
+        log::info!("running solution for day {day}");
+
+        let input = input_file(day, Path::new(INPUT_DIR), website.as_ref(), config.refresh).await;
+        let variants = select_variants(day, config.variant.as_deref());
+
+        if record {
+            visualize::record();
+        }
+
+        let (outcome, elapsed) = match (input, variants) {
+            (Ok(input), Ok(variants)) => run_variants(config, day, variants, &input),
+            (_, Err(e)) => (runner::Outcome::Finished(Err(e)), Duration::ZERO),
+            (Err(e), _) => {
+                let e = anyhow!("failed to get input: {e}");
+                (runner::Outcome::Finished(Err(e)), Duration::ZERO)
+            }
+        };
+
+        if outcome.is_ok() {
+            log::info!("day {day}: {outcome} in {elapsed:?}");
+        } else {
+            log::error!("day {day}: {outcome}");
+        }
+
+        entries.push(report::Entry {
+            day,
+            outcome,
+            elapsed,
+            frames: visualize::recorded(),
+        });
+    }
+
+    Ok(entries)
+}
+
+/// Writes a made-up input for `day` to stdout.
+fn gen(day: Option<u8>, seed: Option<u64>, size: usize) -> anyhow::Result<()> {
+    let Some(day) = day else {
+        bail!("no --day provided to generate input for");
+    };
+
+    let seed = seed.unwrap_or_else(rng::Rng::seed);
+    log::info!("generating input for day {day} with seed {seed}");
+
+    let input = gen::input(day, &mut rng::Rng::new(seed), size)?;
+    io::stdout().write_all(input.as_bytes())?;
+
+    Ok(())
+}
+
+/// Fuzzes the parser called `target`, or every parser, logging inputs that make them panic.
+fn fuzz(target: Option<&str>, iterations: usize, seed: Option<u64>) -> anyhow::Result<()> {
+    let targets = match target {
+        Some(name) => match fuzz::Target::find(name) {
+            Some(target) => slice::from_ref(target),
+            None => bail!("no fuzz target called {name}"),
+        },
+        None => fuzz::TARGETS,
+    };
+
+    let seed = seed.unwrap_or_else(rng::Rng::seed);
+    log::info!("fuzzing with seed {seed}");
+
+    let mut rng = rng::Rng::new(seed);
+    let mut failed = vec![];
+
+    for target in targets {
+        let crashes = target.fuzz(&mut rng, iterations);
+
+        for input in &crashes {
+            log::error!("{} panicked on input {input:?}", target.name);
+        }
+
+        log::info!(
+            "{}: {iterations} inputs, {} panics",
+            target.name,
+            crashes.len()
+        );
+
+        if !crashes.is_empty() {
+            failed.push(target.name);
+        }
+    }
+
+    if !failed.is_empty() {
+        bail!("parsers panicked: {failed:?}");
+    }
+
+    Ok(())
+}
+
+/// Runs each of `variants` of the solution for `day` on the input in the file at `path`, failing
+/// if they do not all give the same answers. Variants that can stream the input read it from the
+/// file as they go. Returns how the first run ended, and how long they took altogether.
+fn run_variants(
+    config: &Config,
+    day: u8,
+    variants: &[runner::Variant],
+    path: &Path,
+) -> (runner::Outcome, Duration) {
+    let mut first: Option<(&str, runner::Outcome)> = None;
+    let mut total = Duration::ZERO;
+
+    for variant in variants {
+        let input = match File::open(path) {
+            Ok(f) => runner::Input::Reader(Box::new(BufReader::new(f))),
+            Err(e) => {
+                let e = anyhow!("failed to open input file {}: {e}", path.display());
+                return (runner::Outcome::Finished(Err(e)), total);
+            }
+        };
+
+        let (outcome, elapsed) = run_solution(config, day, variant, input);
+        total += elapsed;
+
+        if variants.len() > 1 {
+            log::info!(
+                "day {day} {} variant: {outcome} in {elapsed:?}",
+                variant.name
+            );
+        }
+
+        match (&first, &outcome) {
+            (None, _) => first = Some((variant.name, outcome)),
+            (
+                Some((name, runner::Outcome::Finished(Ok(expected)))),
+                runner::Outcome::Finished(Ok(answers)),
+            ) if answers != expected => {
+                let e = anyhow!(
+                    "variants disagree, {name} gave {expected} but {} gave {answers}",
+                    variant.name
                 );
-            };
+                return (runner::Outcome::Finished(Err(e)), total);
+            }
+            (Some(_), runner::Outcome::Finished(Ok(_))) => {}
+            (Some(_), _) => return (outcome, total),
+        }
+    }
 
-            log::debug!("getting input from website");
+    let outcome = first.map_or_else(
+        || runner::Outcome::Finished(Err(anyhow!("no variants to run"))),
+        |(_, outcome)| outcome,
+    );
 
-            const TIMEOUT: Duration = Duration::from_secs(2);
-            let client = reqwest::Client::builder().timeout(TIMEOUT).build()?;
-            let url = format!("https://adventofcode.com/2022/day/{day}/input");
+    (outcome, total)
+}
 
-            let response = client
-                .get(Url::parse(&url)?)
-                .header("cookie", format!("session={session}"))
-                .send()
-                .await?;
+/// Runs a variant of the solution for `day` on its own thread, or in a child process if
+/// sandboxing or if it has to be killed when it runs out of time.
+fn run_solution(
+    config: &Config,
+    day: u8,
+    variant: &runner::Variant,
+    input: runner::Input,
+) -> (runner::Outcome, Duration) {
+    if config.sandbox {
+        sandbox::run(day, variant.name, input, config.limits(), config.timeout)
+    } else if config.timeout.is_some() && visualize::recording().is_none() {
+        // A child process can be killed when its time is up, where a thread can only be left
+        // running. Frames cannot be recorded from one, though.
+        let limits = sandbox::Limits::default();
+        sandbox::run(day, variant.name, input, limits, config.timeout)
+    } else {
+        let name = format!("day{day}-{}", variant.name);
+        runner::run(&name, *variant, input, config.timeout)
+    }
+}
 
-            let Ok(response) = response.error_for_status() else {
-                bail!("got non-200 status code getting input from website");
-            };
+/// Returns the solution for `day`, if there is one. Days with several implementations return the
+/// first.
+pub fn solution_for(day: u8) -> Option<runner::Solution> {
+    variants_for(day).first().map(|variant| variant.solution)
+}
 
-            let input = response.text().await?;
+/// Returns the implementations of the solution for `day`, the default first. Empty if there is no
+/// solution.
+pub fn variants_for(day: u8) -> &'static [runner::Variant] {
+    /// The only implementation of a day's solution, and the same one reading its input as a
+    /// stream if there is one.
+    macro_rules! only {
+        ($solution:path) => {
+            &[runner::Variant {
+                name: runner::Variant::DEFAULT,
+                solution: $solution,
+                stream: None,
+            }]
+        };
+        ($solution:path, $stream:path) => {
+            &[runner::Variant {
+                name: runner::Variant::DEFAULT,
+                solution: $solution,
+                stream: Some($stream),
+            }]
+        };
+    }
+
+    match day {
+        1 => only!(day1::main, day1::main_stream),
+        2 => only!(day2::main),
+        3 => day3::VARIANTS,
+        4 => only!(day4::main),
+        5 => only!(day5::main),
+        6 => day6::VARIANTS,
+        7 => only!(day7::main),
+        8 => day8::VARIANTS,
+        9 => only!(day9::main),
+        10 => only!(day10::main),
+        11 => only!(day11::main),
+        12 => only!(day12::main),
+        13 => only!(day13::main),
+        _ => &[],
+    }
+}
 
-            File::create(filepath)?.write_all(input.as_bytes())?;
+/// Picks the implementations of the solution for `day` to run: the one called `variant`, every
+/// one if it is `all`, or the default if it is not given.
+fn select_variants(day: u8, variant: Option<&str>) -> anyhow::Result<&'static [runner::Variant]> {
+    let variants = variants_for(day);
+    if variants.is_empty() {
+        bail!("solution for day {day} does not exist");
+    }
 
-            input
+    match variant {
+        None => Ok(&variants[..1]),
+        Some("all") => Ok(variants),
+        Some(name) => match variants.iter().position(|variant| variant.name == name) {
+            Some(i) => Ok(&variants[i..=i]),
+            None => {
+                let names: Vec<_> = variants.iter().map(|variant| variant.name).collect();
+                bail!("day {day} has no variant called {name}, only {names:?}");
+            }
+        },
+    }
+}
+
+/// The directory where challenge inputs are cached.
+const INPUT_DIR: &str = "input";
+
+/// Gets the input for a challenge from the cache in `dir`, falling back to the website and caching
+/// what it returns. The cache is skipped if `refresh` is set.
+pub async fn input(
+    day: u8,
+    dir: &Path,
+    website: Option<&Website>,
+    refresh: bool,
+) -> anyhow::Result<String> {
+    let filepath = input_file(day, dir, website, refresh).await?;
+    Ok(fs::read_to_string(filepath)?)
+}
+
+/// Makes sure the input for a challenge is in the cache in `dir` like [`input`] does, without
+/// reading it, and returns the path of the file it is in.
+pub async fn input_file(
+    day: u8,
+    dir: &Path,
+    website: Option<&Website>,
+    refresh: bool,
+) -> anyhow::Result<PathBuf> {
+    let filepath = dir.join(format!("day_{day}.txt"));
+
+    if !refresh {
+        match File::open(&filepath) {
+            Ok(_) => {
+                log::debug!("getting input from file {}", filepath.to_string_lossy());
+
+                return Ok(filepath);
+            }
+            Err(e) if e.kind() == ErrorKind::NotFound => {}
+            Err(e) => bail!(
+                "failed to process input file {}: {e}",
+                filepath.to_string_lossy()
+            ),
         }
+    }
+
+    let Some(website) = website else {
+        bail!(
+            "failed to find input file {} and no session cookie provided to get input from website",
+            filepath.to_string_lossy()
+        );
     };
 
-    Ok(input)
+    log::debug!("getting input from website");
+
+    let input = website.input(day).await?;
+
+    write_atomically(&filepath, &input).map_err(|e| {
+        anyhow!(
+            "failed to cache input in file {}: {e}",
+            filepath.to_string_lossy()
+        )
+    })?;
+
+    Ok(filepath)
+}
+
+/// Writes `contents` to a temporary file next to `path` and renames it into place, so that an
+/// interrupted write never leaves a truncated file at `path`.
+fn write_atomically(path: &Path, contents: &str) -> io::Result<()> {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+
+    let mut temp_path = path.as_os_str().to_owned();
+    temp_path.push(format!(".{}.tmp", process::id()));
+    let temp_path = PathBuf::from(temp_path);
+
+    let result = File::create(&temp_path)
+        .and_then(|mut f| {
+            f.write_all(contents.as_bytes())?;
+            f.sync_all()
+        })
+        .and_then(|_| fs::rename(&temp_path, path));
+
+    if result.is_err() {
+        let _ = fs::remove_file(&temp_path);
+    }
+
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+
+    use super::*;
+
+    #[test]
+    fn session_from_file() {
+        let path = env::temp_dir().join(format!("aoc-session-{}", std::process::id()));
+        fs::write(&path, "abc123\n").unwrap();
+
+        let config =
+            Config::try_parse_from(["aoc", "--session-file", path.to_str().unwrap(), "whoami"])
+                .unwrap();
+        assert_eq!(config.session().unwrap(), Some("abc123".to_string()));
+
+        fs::write(&path, "  \n").unwrap();
+        assert!(config.session().is_err());
+
+        fs::remove_file(&path).unwrap();
+        assert!(config.session().is_err());
+    }
+
+    #[test]
+    fn session_conflicts_with_session_file() {
+        let config = Config::try_parse_from(["aoc", "-s", "abc", "--session-file", "cookie"]);
+        assert!(config.is_err());
+    }
+
+    #[test]
+    fn limits_in_range() {
+        let parse = |arg: &str, value: String| Config::try_parse_from(["aoc", arg, &value]);
+
+        let config = parse("--memory-limit", sandbox::Limits::MAX_MEMORY.to_string()).unwrap();
+        assert_eq!(config.limits().memory, Some(sandbox::Limits::MAX_MEMORY));
+        assert!(parse(
+            "--memory-limit",
+            (sandbox::Limits::MAX_MEMORY + 1).to_string()
+        )
+        .is_err());
+        assert!(parse("--cpu-limit", u64::MAX.to_string()).is_err());
+    }
+
+    #[test]
+    fn memory_limit_only_when_sandboxed() {
+        let limits = |args: &[&str]| Config::try_parse_from(args).unwrap().limits();
+
+        assert_eq!(
+            limits(&["aoc", "--sandbox"]).memory,
+            Some(sandbox::Limits::DEFAULT_MEMORY)
+        );
+        assert_eq!(
+            limits(&["aoc", "--sandbox", "--memory-limit", "64"]).memory,
+            Some(64)
+        );
+
+        // A child process run only so that it can be killed on time is passed no limits
+        let child = ["aoc", "--day", "1", "--variant", "default", "solve"];
+        assert_eq!(limits(&child), sandbox::Limits::default());
+        assert_eq!(
+            limits(&["aoc", "--timeout", "1s"]),
+            sandbox::Limits::default()
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn world_readable() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let path = env::temp_dir().join(format!("aoc-permissions-{}", std::process::id()));
+        let file = File::create(&path).unwrap();
+
+        file.set_permissions(fs::Permissions::from_mode(0o600))
+            .unwrap();
+        assert!(!is_world_readable(&file.metadata().unwrap()));
+
+        file.set_permissions(fs::Permissions::from_mode(0o644))
+            .unwrap();
+        assert!(is_world_readable(&file.metadata().unwrap()));
+
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn select_variant() {
+        let names = |variants: &[runner::Variant]| -> Vec<&str> {
+            variants.iter().map(|variant| variant.name).collect()
+        };
+
+        assert_eq!(names(select_variants(8, None).unwrap()), ["scan"]);
+        assert_eq!(names(select_variants(8, Some("stack")).unwrap()), ["stack"]);
+        assert_eq!(
+            names(select_variants(8, Some("all")).unwrap()),
+            ["scan", "stack"]
+        );
+        assert_eq!(
+            names(select_variants(1, None).unwrap()),
+            [runner::Variant::DEFAULT]
+        );
+
+        let e = select_variants(8, Some("fast")).unwrap_err();
+        assert_eq!(
+            e.to_string(),
+            r#"day 8 has no variant called fast, only ["scan", "stack"]"#
+        );
+        assert!(select_variants(25, None).is_err());
+    }
+
+    #[test]
+    fn write_atomically_creates_directories() {
+        let dir = env::temp_dir().join(format!("aoc-atomic-{}", std::process::id()));
+        let path = dir.join("nested").join("day_1.txt");
+
+        write_atomically(&path, "first").unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), "first");
+
+        write_atomically(&path, "second").unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), "second");
+
+        // Only the input file is left behind
+        assert_eq!(fs::read_dir(path.parent().unwrap()).unwrap().count(), 1);
+
+        fs::remove_dir_all(dir).unwrap();
+    }
 }
@@ -0,0 +1,403 @@
+use std::{
+    fmt,
+    ops::{Add, Div, Mul, Neg, Rem, Sub},
+};
+
+/// The built-in integer types, so number theory can be written once for all of them.
+pub trait Integer:
+    Copy
+    + Ord
+    + fmt::Debug
+    + Add<Output = Self>
+    + Sub<Output = Self>
+    + Mul<Output = Self>
+    + Div<Output = Self>
+    + Rem<Output = Self>
+{
+    const ZERO: Self;
+    const ONE: Self;
+
+    /// The remainder of dividing by `m`, from zero up to `m`, even for negative numbers.
+    fn rem_euclid(self, m: Self) -> Self;
+
+    /// Multiplies, or gives `None` if the product does not fit.
+    fn checked_mul(self, other: Self) -> Option<Self>;
+
+    /// The size of the number without its sign, or `None` if it does not fit, like `-i8::MIN`.
+    fn checked_abs(self) -> Option<Self>;
+
+    /// `self * other` modulo `m`, without the product overflowing.
+    fn mul_mod(self, other: Self, m: Self) -> Self;
+}
+
+macro_rules! integer {
+    // Multiplies in a type twice as wide, which cannot overflow
+    ($($t:ty => $wide:ty, $abs:expr);* $(;)?) => {$(
+        impl Integer for $t {
+            const ZERO: $t = 0;
+            const ONE: $t = 1;
+
+            fn rem_euclid(self, m: $t) -> $t {
+                <$t>::rem_euclid(self, m)
+            }
+
+            fn checked_mul(self, other: $t) -> Option<$t> {
+                <$t>::checked_mul(self, other)
+            }
+
+            fn checked_abs(self) -> Option<$t> {
+                $abs(self)
+            }
+
+            fn mul_mod(self, other: $t, m: $t) -> $t {
+                let product = self.rem_euclid(m) as $wide * other.rem_euclid(m) as $wide;
+                (product % m as $wide) as $t
+            }
+        }
+    )*};
+}
+
+integer! {
+    u8 => u16, Some;
+    u16 => u32, Some;
+    u32 => u64, Some;
+    u64 => u128, Some;
+    usize => u128, Some;
+    i8 => i16, i8::checked_abs;
+    i16 => i32, i16::checked_abs;
+    i32 => i64, i32::checked_abs;
+    i64 => i128, i64::checked_abs;
+    isize => i128, isize::checked_abs;
+}
+
+macro_rules! widest {
+    // Nothing is wider, so these double and add instead
+    ($($t:ty, $abs:expr);* $(;)?) => {$(
+        impl Integer for $t {
+            const ZERO: $t = 0;
+            const ONE: $t = 1;
+
+            fn rem_euclid(self, m: $t) -> $t {
+                <$t>::rem_euclid(self, m)
+            }
+
+            fn checked_mul(self, other: $t) -> Option<$t> {
+                <$t>::checked_mul(self, other)
+            }
+
+            fn checked_abs(self) -> Option<$t> {
+                $abs(self)
+            }
+
+            fn mul_mod(self, other: $t, m: $t) -> $t {
+                let (mut a, mut b) = (self.rem_euclid(m), other.rem_euclid(m));
+                let mut product = 0;
+                while b > 0 {
+                    if b % 2 == 1 {
+                        product = add_mod(product, a, m);
+                    }
+                    a = add_mod(a, a, m);
+                    b /= 2;
+                }
+                product
+            }
+        }
+    )*};
+}
+
+widest! {
+    u128, Some;
+    i128, i128::checked_abs;
+}
+
+/// Panics unless `m` can be a modulus.
+fn check_modulus<T: Integer>(m: T) {
+    assert!(m > T::ZERO, "the modulus must be positive, not {m:?}");
+}
+
+/// The greatest common divisor of `a` and `b`, which is never negative. Panics if it does not fit,
+/// which only happens for `gcd(MIN, 0)`, `gcd(0, MIN)` and `gcd(MIN, MIN)` of signed types.
+pub fn gcd<T: Integer>(mut a: T, mut b: T) -> T {
+    while b != T::ZERO {
+        // `MIN % -1` overflows, though the remainder of dividing by one is always zero
+        if b.checked_abs() == Some(T::ONE) {
+            return T::ONE;
+        }
+        (a, b) = (b, a % b);
+    }
+
+    a.checked_abs()
+        .unwrap_or_else(|| panic!("the gcd of {a:?} does not fit"))
+}
+
+/// The greatest common divisor `g` of `a` and `b`, with coefficients `x` and `y` such that
+/// `a * x + b * y == g`. The coefficients are no bigger than `a` and `b`, so they fit, but like
+/// division it overflows for `MIN` and `-1`.
+pub fn extended_gcd<T: Integer + Neg<Output = T>>(a: T, b: T) -> (T, T, T) {
+    let (mut old_r, mut r) = (a, b);
+    let (mut old_x, mut x) = (T::ONE, T::ZERO);
+    let (mut old_y, mut y) = (T::ZERO, T::ONE);
+
+    while r != T::ZERO {
+        let q = old_r / r;
+        (old_r, r) = (r, old_r % r);
+        (old_x, x) = (x, old_x - q * x);
+        (old_y, y) = (y, old_y - q * y);
+    }
+
+    if old_r < T::ZERO {
+        (-old_r, -old_x, -old_y)
+    } else {
+        (old_r, old_x, old_y)
+    }
+}
+
+/// The least common multiple of `a` and `b`, which is never negative, or `None` if it does not
+/// fit. It is zero if either of them is.
+pub fn lcm<T: Integer>(a: T, b: T) -> Option<T> {
+    if a == T::ZERO || b == T::ZERO {
+        return Some(T::ZERO);
+    }
+
+    (a / gcd(a, b)).checked_mul(b)?.checked_abs()
+}
+
+/// `a + b` modulo `m`, without the sum overflowing. Panics unless `m` is positive.
+pub fn add_mod<T: Integer>(a: T, b: T, m: T) -> T {
+    check_modulus(m);
+    let (a, b) = (a.rem_euclid(m), b.rem_euclid(m));
+
+    // Both are below `m`, so `m - b` fits and so does the answer
+    if a >= m - b {
+        a - (m - b)
+    } else {
+        a + b
+    }
+}
+
+/// `a - b` modulo `m`, without the difference overflowing. Panics unless `m` is positive.
+pub fn sub_mod<T: Integer>(a: T, b: T, m: T) -> T {
+    check_modulus(m);
+    let (a, b) = (a.rem_euclid(m), b.rem_euclid(m));
+
+    if a >= b {
+        a - b
+    } else {
+        a + (m - b)
+    }
+}
+
+/// `a * b` modulo `m`, without the product overflowing. Panics unless `m` is positive.
+pub fn mul_mod<T: Integer>(a: T, b: T, m: T) -> T {
+    check_modulus(m);
+    a.mul_mod(b, m)
+}
+
+/// `base` to the power of `exp` modulo `m`, squaring and multiplying so that nothing overflows.
+/// Panics unless `m` is positive.
+pub fn pow_mod<T: Integer>(base: T, mut exp: u64, m: T) -> T {
+    check_modulus(m);
+
+    let mut base = base.rem_euclid(m);
+    let mut power = T::ONE.rem_euclid(m);
+    while exp > 0 {
+        if exp % 2 == 1 {
+            power = power.mul_mod(base, m);
+        }
+        base = base.mul_mod(base, m);
+        exp /= 2;
+    }
+
+    power
+}
+
+/// The number that gives one when multiplied by `a` modulo `m`, or `None` if there isn't one
+/// because `a` and `m` share a factor. Panics unless `m` is positive.
+pub fn mod_inverse<T: Integer>(a: T, m: T) -> Option<T> {
+    check_modulus(m);
+
+    // The extended Euclidean algorithm, keeping only the coefficient of `a` and keeping it modulo
+    // `m` so that it never goes negative, which unsigned types could not hold
+    let (mut old_r, mut r) = (a.rem_euclid(m), m);
+    let (mut old_x, mut x) = (T::ONE.rem_euclid(m), T::ZERO);
+
+    while r != T::ZERO {
+        let q = old_r / r;
+        (old_r, r) = (r, old_r % r);
+        (old_x, x) = (x, sub_mod(old_x, q.mul_mod(x, m), m));
+    }
+
+    (old_r == T::ONE).then_some(old_x)
+}
+
+/// Solves `x ≡ residue (mod modulus)` for every pair in `congruences` at once with the Chinese
+/// remainder theorem, giving the smallest `x` that is not negative and the modulus of the
+/// solution, which is the least common multiple of the moduli. The moduli do not have to be
+/// coprime. Gives `None` if there is no solution or its modulus does not fit. Panics unless every
+/// modulus is positive.
+pub fn crt<T: Integer>(congruences: impl IntoIterator<Item = (T, T)>) -> Option<(T, T)> {
+    let (mut x, mut m) = (T::ZERO, T::ONE);
+
+    for (residue, modulus) in congruences {
+        check_modulus(modulus);
+
+        // Find the `k` for which `x + m * k` solves this congruence as well as the ones before
+        let g = gcd(m, modulus);
+        let difference = sub_mod(residue, x, modulus);
+        if difference % g != T::ZERO {
+            return None;
+        }
+
+        let step = modulus / g;
+        let k = (difference / g).mul_mod(mod_inverse(m / g, step)?, step);
+
+        // `m * k` is below `m * step`, the new modulus, so it cannot overflow if that fits
+        let lcm = (m / g).checked_mul(modulus)?;
+        x = add_mod(x, m * k, lcm);
+        m = lcm;
+    }
+
+    Some((x, m))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn greatest_common_divisor() {
+        assert_eq!(gcd(12u32, 18), 6);
+        assert_eq!(gcd(-12i32, 18), 6);
+        assert_eq!(gcd(12i64, -18), 6);
+        assert_eq!(gcd(0u8, 7), 7);
+        assert_eq!(gcd(0u8, 0), 0);
+        assert_eq!(gcd(i8::MIN, 6), 2);
+        assert_eq!(gcd(u128::MAX, 5), 5);
+
+        // Dividing `MIN` by -1 overflows at every width
+        assert_eq!(gcd(i8::MIN, -1), 1);
+        assert_eq!(gcd(i16::MIN, -1), 1);
+        assert_eq!(gcd(i32::MIN, -1), 1);
+        assert_eq!(gcd(i64::MIN, -1), 1);
+        assert_eq!(gcd(i128::MIN, -1), 1);
+        assert_eq!(gcd(isize::MIN, -1), 1);
+        assert_eq!(gcd(-1, i64::MIN), 1);
+        assert_eq!(gcd(i64::MIN, 1), 1);
+
+        for a in -50i16..=50 {
+            for b in -50i16..=50 {
+                let (g, x, y) = extended_gcd(a, b);
+                assert_eq!(g, gcd(a, b), "gcd of {a} and {b}");
+                assert_eq!(a * x + b * y, g, "coefficients of {a} and {b}");
+            }
+        }
+
+        assert_eq!(extended_gcd(240i32, 46), (2, -9, 47));
+        let (g, x, y) = extended_gcd(i64::MAX, i64::MAX - 1);
+        assert_eq!(g, 1);
+        assert_eq!(
+            i128::from(i64::MAX) * i128::from(x) + i128::from(i64::MAX - 1) * i128::from(y),
+            1
+        );
+    }
+
+    #[test]
+    fn least_common_multiple() {
+        assert_eq!(lcm(4u32, 6), Some(12));
+        assert_eq!(lcm(-4i32, 6), Some(12));
+        assert_eq!(lcm(0u64, 6), Some(0));
+        assert_eq!(lcm(200u8, 3), None);
+        assert_eq!(lcm(u64::MAX, u64::MAX), Some(u64::MAX));
+        assert_eq!(lcm(i8::MIN, 1), None);
+        assert_eq!(lcm(i8::MIN, -1), None);
+        assert_eq!(lcm(-1, i64::MIN), None);
+        assert_eq!(lcm(i128::MIN, -1), None);
+    }
+
+    #[test]
+    fn modular_arithmetic() {
+        assert_eq!(add_mod(250u8, 10, 255), 5);
+        assert_eq!(add_mod(-3i32, 1, 5), 3);
+        assert_eq!(sub_mod(1u8, 2, 255), 254);
+        assert_eq!(
+            sub_mod(i64::MIN, i64::MAX, i64::MAX),
+            i64::MIN.rem_euclid(i64::MAX)
+        );
+
+        assert_eq!(mul_mod(u64::MAX - 1, u64::MAX - 2, u64::MAX), 2);
+        assert_eq!(mul_mod(u128::MAX - 1, u128::MAX - 2, u128::MAX), 2);
+        assert_eq!(mul_mod(i128::MIN, -1, i128::MAX), 1);
+        assert_eq!(mul_mod(-2i8, 3, 7), 1);
+
+        for m in 1..=30u128 {
+            for a in 0..60u128 {
+                for b in 0..60u128 {
+                    assert_eq!(mul_mod(a, b, m), a * b % m, "{a} * {b} mod {m}");
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn modular_exponentiation() {
+        const PRIME: u64 = 1_000_000_007;
+
+        assert_eq!(pow_mod(2u32, 10, 1000), 24);
+        assert_eq!(pow_mod(-2i32, 3, 7), 6);
+        assert_eq!(pow_mod(5u8, 0, 1), 0);
+        assert_eq!(pow_mod(123_456_789u64, PRIME - 1, PRIME), 1);
+        assert_eq!(pow_mod(3u128, 200, u128::MAX), {
+            let mut power = 1u128;
+            for _ in 0..200 {
+                power = mul_mod(power, 3, u128::MAX);
+            }
+            power
+        });
+    }
+
+    #[test]
+    fn modular_inverse() {
+        assert_eq!(mod_inverse(3u32, 11), Some(4));
+        assert_eq!(mod_inverse(-3i32, 11), Some(7));
+        assert_eq!(mod_inverse(6u32, 9), None);
+        assert_eq!(mod_inverse(5u8, 1), Some(0));
+
+        for m in 1..=100u8 {
+            for a in 0..=255u8 {
+                let brute = (0..m).find(|&x| mul_mod(a, x, m) == 1 % m);
+                assert_eq!(mod_inverse(a, m), brute, "inverse of {a} mod {m}");
+            }
+        }
+
+        let m = u64::MAX - 58; // The largest prime that fits
+        let inverse = mod_inverse(u64::MAX, m).unwrap();
+        assert_eq!(mul_mod(inverse, u64::MAX, m), 1);
+    }
+
+    #[test]
+    #[should_panic(expected = "the modulus must be positive")]
+    fn negative_modulus() {
+        mod_inverse(3i32, -11);
+    }
+
+    #[test]
+    fn chinese_remainders() {
+        assert_eq!(crt([(2u32, 3), (3, 5), (2, 7)]), Some((23, 105)));
+        assert_eq!(crt([(-1i32, 3), (-2, 5)]), Some((8, 15)));
+        assert_eq!(crt([(2u32, 4), (4, 6)]), Some((10, 12)));
+        assert_eq!(crt([(1u32, 4), (2, 6)]), None);
+        assert_eq!(crt(Vec::<(u8, u8)>::new()), Some((0, 1)));
+        assert_eq!(crt([(0u8, 17), (0, 19)]), None);
+
+        let (x, m) = crt([(3u64, 4_294_967_291), (5, 4_294_967_279)]).unwrap();
+        assert_eq!(m, 4_294_967_291 * 4_294_967_279);
+        assert_eq!((x % 4_294_967_291, x % 4_294_967_279), (3, 5));
+
+        for a in 0..12u16 {
+            for b in 0..18u16 {
+                let brute = (0..36).find(|x| x % 12 == a && x % 18 == b);
+                assert_eq!(crt([(a, 12), (b, 18)]), brute.map(|x| (x, 36)));
+            }
+        }
+    }
+}
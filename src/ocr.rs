@@ -0,0 +1,311 @@
+use anyhow::bail;
+
+use crate::grid::Grid;
+
+/// A block letter font: how wide each letter's cell is, including the gap after it, and what the
+/// letters look like, row by row with `#` for lit pixels.
+struct Font {
+    height: usize,
+    width: usize,
+    cell: usize,
+    letters: &'static [(char, &'static [&'static str])],
+}
+
+/// The font most puzzles draw in, four pixels wide and six high with a gap of one.
+const SMALL: Font = Font {
+    height: 6,
+    width: 4,
+    cell: 5,
+    letters: &[
+        ('A', &[".##.", "#..#", "#..#", "####", "#..#", "#..#"]),
+        ('B', &["###.", "#..#", "###.", "#..#", "#..#", "###."]),
+        ('C', &[".##.", "#..#", "#...", "#...", "#..#", ".##."]),
+        ('E', &["####", "#...", "###.", "#...", "#...", "####"]),
+        ('F', &["####", "#...", "###.", "#...", "#...", "#..."]),
+        ('G', &[".##.", "#..#", "#...", "#.##", "#..#", ".###"]),
+        ('H', &["#..#", "#..#", "####", "#..#", "#..#", "#..#"]),
+        ('I', &[".###", "..#.", "..#.", "..#.", "..#.", ".###"]),
+        ('J', &["..##", "...#", "...#", "...#", "#..#", ".##."]),
+        ('K', &["#..#", "#.#.", "##..", "#.#.", "#.#.", "#..#"]),
+        ('L', &["#...", "#...", "#...", "#...", "#...", "####"]),
+        ('O', &[".##.", "#..#", "#..#", "#..#", "#..#", ".##."]),
+        ('P', &["###.", "#..#", "#..#", "###.", "#...", "#..."]),
+        ('R', &["###.", "#..#", "#..#", "###.", "#.#.", "#..#"]),
+        ('S', &[".###", "#...", "#...", ".##.", "...#", "###."]),
+        ('U', &["#..#", "#..#", "#..#", "#..#", "#..#", ".##."]),
+        ('Y', &["#...", "#...", ".#.#", "..#.", "..#.", "..#."]),
+        ('Z', &["####", "...#", "..#.", ".#..", "#...", "####"]),
+    ],
+};
+
+/// The font some puzzles draw in, six pixels wide and ten high with a gap of two.
+const LARGE: Font = Font {
+    height: 10,
+    width: 6,
+    cell: 8,
+    letters: &[
+        (
+            'A',
+            &[
+                "..##..", ".#..#.", "#....#", "#....#", "#....#", "######", "#....#", "#....#",
+                "#....#", "#....#",
+            ],
+        ),
+        (
+            'B',
+            &[
+                "#####.", "#....#", "#....#", "#....#", "#####.", "#....#", "#....#", "#....#",
+                "#....#", "#####.",
+            ],
+        ),
+        (
+            'C',
+            &[
+                ".####.", "#....#", "#.....", "#.....", "#.....", "#.....", "#.....", "#.....",
+                "#....#", ".####.",
+            ],
+        ),
+        (
+            'E',
+            &[
+                "######", "#.....", "#.....", "#.....", "#####.", "#.....", "#.....", "#.....",
+                "#.....", "######",
+            ],
+        ),
+        (
+            'F',
+            &[
+                "######", "#.....", "#.....", "#.....", "#####.", "#.....", "#.....", "#.....",
+                "#.....", "#.....",
+            ],
+        ),
+        (
+            'G',
+            &[
+                ".####.", "#....#", "#.....", "#.....", "#.....", "#..###", "#....#", "#....#",
+                "#...##", ".###.#",
+            ],
+        ),
+        (
+            'H',
+            &[
+                "#....#", "#....#", "#....#", "#....#", "######", "#....#", "#....#", "#....#",
+                "#....#", "#....#",
+            ],
+        ),
+        (
+            'J',
+            &[
+                "...###", "....#.", "....#.", "....#.", "....#.", "....#.", "....#.", "#...#.",
+                "#...#.", ".###..",
+            ],
+        ),
+        (
+            'K',
+            &[
+                "#....#", "#...#.", "#..#..", "#.#...", "##....", "##....", "#.#...", "#..#..",
+                "#...#.", "#....#",
+            ],
+        ),
+        (
+            'L',
+            &[
+                "#.....", "#.....", "#.....", "#.....", "#.....", "#.....", "#.....", "#.....",
+                "#.....", "######",
+            ],
+        ),
+        (
+            'N',
+            &[
+                "#....#", "##...#", "##...#", "#.#..#", "#.#..#", "#..#.#", "#..#.#", "#...##",
+                "#...##", "#....#",
+            ],
+        ),
+        (
+            'P',
+            &[
+                "#####.", "#....#", "#....#", "#....#", "#####.", "#.....", "#.....", "#.....",
+                "#.....", "#.....",
+            ],
+        ),
+        (
+            'R',
+            &[
+                "#####.", "#....#", "#....#", "#....#", "#####.", "#..#..", "#...#.", "#...#.",
+                "#....#", "#....#",
+            ],
+        ),
+        (
+            'X',
+            &[
+                "#....#", "#....#", ".#..#.", ".#..#.", "..##..", "..##..", ".#..#.", ".#..#.",
+                "#....#", "#....#",
+            ],
+        ),
+        (
+            'Z',
+            &[
+                "######", ".....#", ".....#", "....#.", "...#..", "..#...", ".#....", "#.....",
+                "#.....", "######",
+            ],
+        ),
+    ],
+};
+
+/// Reads the block letters in a picture, picking the font by the height of the picture once
+/// blank rows above and below are left out. The first letter must start at the left edge, and
+/// blank cells between letters read as spaces. Fails on anything that is not a letter of the font.
+pub fn read(picture: &Grid<bool>) -> anyhow::Result<String> {
+    let lit_rows: Vec<&[bool]> = picture
+        .rows()
+        .skip_while(|row| !row.contains(&true))
+        .collect();
+    let Some(height) = lit_rows.iter().rposition(|row| row.contains(&true)) else {
+        bail!("there are no letters in the picture");
+    };
+    let rows = &lit_rows[..=height];
+
+    let Some(font) = [SMALL, LARGE]
+        .into_iter()
+        .find(|font| font.height == rows.len())
+    else {
+        bail!("letters {} pixels high are not in any font", rows.len());
+    };
+
+    let mut text = String::new();
+
+    for start in (0..picture.width()).step_by(font.cell) {
+        let glyph: String = rows
+            .iter()
+            .flat_map(|row| {
+                (start..start + font.width).map(|col| match row.get(col) {
+                    Some(true) => '#',
+                    _ => '.',
+                })
+            })
+            .collect();
+
+        // Anything lit in the gap means the letters are not where the font puts them
+        let gap = rows.iter().any(|row| {
+            row.iter()
+                .skip(start + font.width)
+                .take(font.cell - font.width)
+                .any(|&lit| lit)
+        });
+        if gap {
+            bail!("the letters are not spaced out like the font's");
+        }
+
+        if !glyph.contains('#') {
+            text.push(' ');
+            continue;
+        }
+
+        match font.letters.iter().find(|(_, rows)| rows.concat() == glyph) {
+            Some(&(letter, _)) => text.push(letter),
+            None => bail!(
+                "letter {} is not in the font:\n{}",
+                text.len() + 1,
+                glyph
+                    .as_bytes()
+                    .chunks(font.width)
+                    .map(|row| String::from_utf8_lossy(row) + "\n")
+                    .collect::<String>()
+            ),
+        }
+    }
+
+    Ok(text.trim().to_string())
+}
+
+/// Reads the block letters in a picture drawn with `#` for lit pixels, one row per line.
+pub fn read_text(picture: &str) -> anyhow::Result<String> {
+    read(&Grid::parse(picture, |pixel| Ok(pixel == '#'))?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Draws `text` in `font` the way puzzles do, with `#` for lit pixels.
+    fn draw(font: &Font, text: &str) -> String {
+        let letters: Vec<&[&str]> = text
+            .chars()
+            .map(|c| {
+                font.letters
+                    .iter()
+                    .find(|(letter, _)| *letter == c)
+                    .map_or(&[][..], |(_, rows)| rows)
+            })
+            .collect();
+
+        (0..font.height)
+            .map(|row| {
+                let mut line: String = letters
+                    .iter()
+                    .map(|glyph| {
+                        let pixels = match glyph.get(row) {
+                            Some(pixels) => pixels.to_string(),
+                            None => ".".repeat(font.width),
+                        };
+                        pixels + &".".repeat(font.cell - font.width)
+                    })
+                    .collect();
+                line.push('\n');
+                line
+            })
+            .collect()
+    }
+
+    #[test]
+    fn fonts() {
+        for font in [SMALL, LARGE] {
+            for (letter, rows) in font.letters {
+                assert_eq!(rows.len(), font.height, "{letter}");
+                assert!(rows.iter().all(|row| row.len() == font.width), "{letter}");
+            }
+        }
+    }
+
+    #[test]
+    fn read_small_letters() {
+        let alphabet: String = SMALL.letters.iter().map(|(letter, _)| letter).collect();
+        assert_eq!(read_text(&draw(&SMALL, &alphabet)).unwrap(), alphabet);
+
+        let picture = "\
+            ###..#....###...##..####.###...##..#....\n\
+            #..#.#....#..#.#..#.#....#..#.#..#.#....\n\
+            #..#.#....#..#.#..#.###..###..#....#....\n\
+            ###..#....###..####.#....#..#.#....#....\n\
+            #....#....#....#..#.#....#..#.#..#.#....\n\
+            #....####.#....#..#.#....###...##..####.\n";
+        assert_eq!(read_text(picture).unwrap(), "PLPAFBCL");
+    }
+
+    #[test]
+    fn read_large_letters() {
+        let alphabet: String = LARGE.letters.iter().map(|(letter, _)| letter).collect();
+        assert_eq!(read_text(&draw(&LARGE, &alphabet)).unwrap(), alphabet);
+
+        // Blank rows around the letters and blank cells after them are left out
+        let picture = format!("{0}{1}{0}", ".".repeat(32) + "\n", draw(&LARGE, "NX  "));
+        assert_eq!(read_text(&picture).unwrap(), "NX");
+    }
+
+    #[test]
+    fn unreadable() {
+        assert!(read_text("....\n....\n").is_err());
+        assert!(read_text("#\n#\n#\n").is_err());
+
+        let error = read_text(&draw(&SMALL, "AB").replacen("###.", "####", 1)).unwrap_err();
+        assert!(error
+            .to_string()
+            .starts_with("letter 2 is not in the font:\n####\n"));
+
+        let shifted: String = draw(&SMALL, "HH")
+            .lines()
+            .map(|line| format!(".{line}\n"))
+            .collect();
+        assert!(read_text(&shifted).is_err());
+    }
+}
@@ -0,0 +1,346 @@
+use std::{fmt, str::FromStr};
+
+/// Why a line could not be parsed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Reason {
+    /// Some fixed text was not where it should be.
+    Expected(String),
+    /// A field did not hold what it should.
+    Invalid {
+        field: String,
+        value: String,
+        error: String,
+    },
+    /// The line went on after everything had been read.
+    Trailing(String),
+}
+
+/// A line that could not be parsed, with where and why.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    pub line: String,
+    /// The character the problem starts at, counting from zero.
+    pub column: usize,
+    pub reason: Reason,
+}
+
+impl ParseError {
+    /// An error `at` a byte offset in `line`.
+    fn new(line: &str, at: usize, reason: Reason) -> ParseError {
+        ParseError {
+            line: line.to_string(),
+            column: line[..at].chars().count(),
+            reason,
+        }
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (line, column) = (&self.line, self.column + 1);
+        match &self.reason {
+            Reason::Expected(text) => {
+                write!(f, "expected `{text}` at column {column} of `{line}`")
+            }
+            Reason::Invalid {
+                field,
+                value,
+                error,
+            } => write!(
+                f,
+                "invalid {field} `{value}` at column {column} of `{line}`: {error}"
+            ),
+            Reason::Trailing(text) => {
+                write!(f, "unexpected `{text}` at column {column} of `{line}`")
+            }
+        }
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+/// The paragraphs of `input`, which are separated by blank lines, without the line breaks around
+/// them. Runs of blank lines and blank lines at either end make no empty paragraphs.
+pub fn paragraphs(input: &str) -> impl Iterator<Item = &str> {
+    input
+        .split("\n\n")
+        .map(|paragraph| paragraph.trim_matches('\n'))
+        .filter(|paragraph| !paragraph.is_empty())
+}
+
+/// Every integer in `line`, in order, like the 1, 5 and -3 in `x=1..5, dy=-3`. A minus sign only
+/// counts when it is not straight after a letter or digit, so `2-4` is a range of two positive
+/// numbers. Fails if an integer does not fit in `T`, or is negative and `T` is unsigned.
+pub fn integers<T>(line: &str) -> Result<Vec<T>, ParseError>
+where
+    T: FromStr,
+    T::Err: fmt::Display,
+{
+    let bytes = line.as_bytes();
+    let mut integers = Vec::new();
+    let mut i = 0;
+
+    while i < bytes.len() {
+        if !bytes[i].is_ascii_digit() {
+            i += 1;
+            continue;
+        }
+
+        let signed =
+            i > 0 && bytes[i - 1] == b'-' && (i < 2 || !bytes[i - 2].is_ascii_alphanumeric());
+        let start = if signed { i - 1 } else { i };
+        while i < bytes.len() && bytes[i].is_ascii_digit() {
+            i += 1;
+        }
+
+        let value = &line[start..i];
+        integers.push(value.parse().map_err(|error: T::Err| {
+            ParseError::new(
+                line,
+                start,
+                Reason::Invalid {
+                    field: "integer".to_string(),
+                    value: value.to_string(),
+                    error: error.to_string(),
+                },
+            )
+        })?);
+    }
+
+    Ok(integers)
+}
+
+/// Splits a line like `Starting items: 79, 98` into its key and value at the first `separator`,
+/// trimming the whitespace around both.
+pub fn key_value<'a>(line: &'a str, separator: &str) -> Result<(&'a str, &'a str), ParseError> {
+    let (key, value) = line.split_once(separator).ok_or_else(|| {
+        ParseError::new(line, line.len(), Reason::Expected(separator.to_string()))
+    })?;
+
+    Ok((key.trim(), value.trim()))
+}
+
+/// A pattern for lines like `move {quantity} from {start} to {end}`: fixed text with named fields
+/// in braces. Each field runs up to the first place the fixed text after it shows up, or to the
+/// end of the line if it comes last, so two fields cannot be next to each other.
+#[derive(Debug, Clone, Copy)]
+pub struct Template(&'static str);
+
+/// A piece of a template.
+#[derive(Debug, Clone, Copy)]
+enum Piece {
+    Text(&'static str),
+    Field(&'static str),
+}
+
+impl Template {
+    pub const fn new(pattern: &'static str) -> Template {
+        Template(pattern)
+    }
+
+    /// Splits the pattern into its fixed text and fields.
+    fn pieces(&self) -> Vec<Piece> {
+        let mut pieces = Vec::new();
+        let mut rest = self.0;
+
+        while let Some(open) = rest.find('{') {
+            let close = rest[open..]
+                .find('}')
+                .unwrap_or_else(|| panic!("unclosed field in template `{}`", self.0));
+            if open > 0 {
+                pieces.push(Piece::Text(&rest[..open]));
+            } else if let Some(Piece::Field(_)) = pieces.last() {
+                panic!("fields in template `{}` need text between them", self.0);
+            }
+            pieces.push(Piece::Field(&rest[open + 1..open + close]));
+            rest = &rest[open + close + 1..];
+        }
+        if !rest.is_empty() {
+            pieces.push(Piece::Text(rest));
+        }
+
+        pieces
+    }
+
+    /// Matches `line` against the template, giving the text of each field. Fails if the fixed text
+    /// is not all there or the line goes on past the end of the template.
+    pub fn captures<'a>(&self, line: &'a str) -> Result<Fields<'a>, ParseError> {
+        let pieces = self.pieces();
+        let mut fields = Vec::new();
+        let mut at = 0;
+
+        for (i, piece) in pieces.iter().enumerate() {
+            match *piece {
+                Piece::Text(text) => {
+                    if !line[at..].starts_with(text) {
+                        return Err(ParseError::new(line, at, Reason::Expected(text.into())));
+                    }
+                    at += text.len();
+                }
+                Piece::Field(name) => {
+                    let end = match pieces.get(i + 1) {
+                        Some(Piece::Text(text)) => {
+                            at + line[at..].find(text).ok_or_else(|| {
+                                ParseError::new(
+                                    line,
+                                    line.len(),
+                                    Reason::Expected(text.to_string()),
+                                )
+                            })?
+                        }
+                        _ => line.len(),
+                    };
+                    fields.push((name, &line[at..end], at));
+                    at = end;
+                }
+            }
+        }
+
+        if at < line.len() {
+            return Err(ParseError::new(
+                line,
+                at,
+                Reason::Trailing(line[at..].to_string()),
+            ));
+        }
+
+        Ok(Fields { line, fields })
+    }
+}
+
+/// The fields a [`Template`] found in a line.
+#[derive(Debug, Clone)]
+pub struct Fields<'a> {
+    line: &'a str,
+    /// The name, text and byte offset of each field.
+    fields: Vec<(&'static str, &'a str, usize)>,
+}
+
+impl<'a> Fields<'a> {
+    /// The text of the field called `name`, if the template has one.
+    pub fn get(&self, name: &str) -> Option<&'a str> {
+        self.fields
+            .iter()
+            .find(|(field, _, _)| *field == name)
+            .map(|&(_, value, _)| value)
+    }
+
+    /// Parses the field called `name`. Panics if the template has no such field, since that is a
+    /// mistake in the template rather than the input.
+    pub fn parse<T>(&self, name: &str) -> Result<T, ParseError>
+    where
+        T: FromStr,
+        T::Err: fmt::Display,
+    {
+        let &(_, value, at) = self
+            .fields
+            .iter()
+            .find(|(field, _, _)| *field == name)
+            .unwrap_or_else(|| panic!("the template has no field called {name}"));
+
+        value.parse().map_err(|error: T::Err| {
+            ParseError::new(
+                self.line,
+                at,
+                Reason::Invalid {
+                    field: name.to_string(),
+                    value: value.to_string(),
+                    error: error.to_string(),
+                },
+            )
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MOVE: Template = Template::new("move {quantity} from {start} to {end}");
+
+    #[test]
+    fn split_paragraphs() {
+        let input = "\n1\n2\n\n3\n\n\n\n4\n";
+        assert_eq!(paragraphs(input).collect::<Vec<_>>(), ["1\n2", "3", "4"]);
+        assert_eq!(paragraphs("").count(), 0);
+    }
+
+    #[test]
+    fn find_integers() {
+        assert_eq!(integers::<i64>("x=1..5, dy=-3").unwrap(), [1, 5, -3]);
+        assert_eq!(integers::<u32>("2-4,6-8").unwrap(), [2, 4, 6, 8]);
+        assert_eq!(integers::<i32>("-7 a-1 -").unwrap(), [-7, 1]);
+        assert_eq!(integers::<u8>("no numbers").unwrap(), []);
+
+        let error = integers::<u8>("items: 79, 256").unwrap_err();
+        assert_eq!(error.column, 11);
+        assert_eq!(
+            error.to_string(),
+            "invalid integer `256` at column 12 of `items: 79, 256`: number too large to fit in target type"
+        );
+        assert!(integers::<u32>("x=-3").is_err());
+    }
+
+    #[test]
+    fn split_key_values() {
+        assert_eq!(
+            key_value("  Starting items: 79, 98", ":").unwrap(),
+            ("Starting items", "79, 98")
+        );
+        assert_eq!(key_value("a = b = c", "=").unwrap(), ("a", "b = c"));
+
+        let error = key_value("no separator", ":").unwrap_err();
+        assert_eq!(error.reason, Reason::Expected(":".to_string()));
+        assert_eq!(error.column, 12);
+    }
+
+    #[test]
+    fn match_templates() {
+        let fields = MOVE.captures("move 12 from 3 to 10").unwrap();
+        assert_eq!(fields.get("quantity"), Some("12"));
+        assert_eq!(fields.get("crates"), None);
+        assert_eq!(fields.parse::<usize>("end").unwrap(), 10);
+
+        let fields = Template::new("{key}: {value}")
+            .captures("Monkey 10: ")
+            .unwrap();
+        assert_eq!(
+            (fields.get("key"), fields.get("value")),
+            (Some("Monkey 10"), Some(""))
+        );
+
+        let error = fields.parse::<u8>("value").unwrap_err();
+        assert_eq!(error.column, 11);
+        assert!(matches!(error.reason, Reason::Invalid { ref field, .. } if field == "value"));
+    }
+
+    #[test]
+    fn template_errors() {
+        let error = MOVE.captures("mv 1 from 2 to 3").unwrap_err();
+        assert_eq!(error.reason, Reason::Expected("move ".to_string()));
+        assert_eq!(error.column, 0);
+
+        let error = MOVE.captures("move 1 to 3").unwrap_err();
+        assert_eq!(error.reason, Reason::Expected(" from ".to_string()));
+
+        let error = MOVE
+            .captures("move x from 2 to 3")
+            .unwrap()
+            .parse::<usize>("quantity")
+            .unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "invalid quantity `x` at column 6 of `move x from 2 to 3`: invalid digit found in string"
+        );
+
+        let error = Template::new("noop").captures("noop 1").unwrap_err();
+        assert_eq!(error.reason, Reason::Trailing(" 1".to_string()));
+        assert_eq!(error.to_string(), "unexpected ` 1` at column 5 of `noop 1`");
+    }
+
+    #[test]
+    #[should_panic(expected = "need text between them")]
+    fn adjacent_fields() {
+        let _ = Template::new("{a}{b}").captures("ab");
+    }
+}
@@ -0,0 +1,242 @@
+use std::{
+    cmp::Reverse,
+    collections::{BinaryHeap, HashMap, VecDeque},
+    hash::Hash,
+};
+
+/// Something to find paths through, like a map of squares with the moves between them.
+pub trait Graph {
+    /// A place a path can go through, like a square on a map.
+    type Node: Clone + Eq + Hash;
+
+    /// The nodes one move away from `node`, with what each move costs.
+    fn neighbours(&self, node: &Self::Node) -> Vec<(Self::Node, u64)>;
+}
+
+/// What a search found: the cheapest cost of reaching every node it reached, and the way there.
+#[derive(Debug, Clone)]
+pub struct Search<N> {
+    costs: HashMap<N, u64>,
+    /// The node each reached node was reached from. Starts have none.
+    previous: HashMap<N, N>,
+    /// The goal the search stopped at, if it found one.
+    goal: Option<N>,
+}
+
+impl<N: Clone + Eq + Hash> Search<N> {
+    fn new(starts: &[N]) -> Search<N> {
+        Search {
+            costs: starts.iter().map(|start| (start.clone(), 0)).collect(),
+            previous: HashMap::new(),
+            goal: None,
+        }
+    }
+
+    /// The goal the search stopped at, if it reached one.
+    pub fn goal(&self) -> Option<&N> {
+        self.goal.as_ref()
+    }
+
+    /// The cost of the cheapest path to `node` from the nearest start, if the search reached it.
+    /// Nodes the search was still weighing up when it reached the goal may not be the cheapest yet.
+    pub fn cost(&self, node: &N) -> Option<u64> {
+        self.costs.get(node).copied()
+    }
+
+    /// The cost of the cheapest path to the goal, if the search reached one.
+    pub fn goal_cost(&self) -> Option<u64> {
+        self.goal.as_ref().and_then(|goal| self.cost(goal))
+    }
+
+    /// Every node the search reached, with the cost of reaching it.
+    pub fn costs(&self) -> &HashMap<N, u64> {
+        &self.costs
+    }
+
+    /// The nodes along the cheapest path to `node`, from a start to `node` itself, if the search
+    /// reached it.
+    pub fn path_to(&self, node: &N) -> Option<Vec<N>> {
+        self.costs.get(node)?;
+
+        let mut path = vec![node.clone()];
+        while let Some(previous) = self.previous.get(path.last()?) {
+            path.push(previous.clone());
+        }
+        path.reverse();
+
+        Some(path)
+    }
+
+    /// The nodes along the cheapest path to the goal, if the search reached one.
+    pub fn goal_path(&self) -> Option<Vec<N>> {
+        self.goal.as_ref().and_then(|goal| self.path_to(goal))
+    }
+}
+
+/// Searches breadth first from every one of `starts` at once, counting moves and ignoring their
+/// costs, until reaching a node that `is_goal`. Searches everything reachable if there is no goal.
+pub fn bfs<G: Graph>(
+    graph: &G,
+    starts: impl IntoIterator<Item = G::Node>,
+    mut is_goal: impl FnMut(&G::Node) -> bool,
+) -> Search<G::Node> {
+    // Starts are searched from in order, so ties go to the first
+    let starts: Vec<_> = starts.into_iter().collect();
+    let mut search = Search::new(&starts);
+    let mut queue = VecDeque::from(starts);
+
+    while let Some(node) = queue.pop_front() {
+        if is_goal(&node) {
+            search.goal = Some(node);
+            break;
+        }
+
+        let moves = search.costs[&node] + 1;
+        for (next, _) in graph.neighbours(&node) {
+            if !search.costs.contains_key(&next) {
+                search.costs.insert(next.clone(), moves);
+                search.previous.insert(next.clone(), node.clone());
+                queue.push_back(next);
+            }
+        }
+    }
+
+    search
+}
+
+/// Searches cheapest first from every one of `starts` at once, until reaching a node that
+/// `is_goal`. Searches everything reachable if there is no goal.
+pub fn dijkstra<G: Graph>(
+    graph: &G,
+    starts: impl IntoIterator<Item = G::Node>,
+    is_goal: impl FnMut(&G::Node) -> bool,
+) -> Search<G::Node> {
+    astar(graph, starts, is_goal, |_| 0)
+}
+
+/// Searches from every one of `starts` at once, trying first the nodes that `heuristic` reckons
+/// are on the cheapest paths, until reaching a node that `is_goal`. The heuristic must never
+/// overestimate what reaching a goal from a node costs, or the path found may not be the cheapest.
+pub fn astar<G: Graph>(
+    graph: &G,
+    starts: impl IntoIterator<Item = G::Node>,
+    mut is_goal: impl FnMut(&G::Node) -> bool,
+    mut heuristic: impl FnMut(&G::Node) -> u64,
+) -> Search<G::Node> {
+    let starts: Vec<_> = starts.into_iter().collect();
+    let mut search = Search::new(&starts);
+
+    // Nodes are not ordered, so the heap holds where they are in `nodes` instead. Ties go to the
+    // node queued first.
+    let mut nodes: Vec<(G::Node, u64)> = Vec::new();
+    let mut queue = BinaryHeap::new();
+
+    for start in starts {
+        queue.push(Reverse((heuristic(&start), nodes.len())));
+        nodes.push((start, 0));
+    }
+
+    while let Some(Reverse((_, i))) = queue.pop() {
+        let (node, cost) = nodes[i].clone();
+
+        // A cheaper way here was found after this one was queued
+        if search.costs[&node] < cost {
+            continue;
+        }
+
+        if is_goal(&node) {
+            search.goal = Some(node);
+            break;
+        }
+
+        for (next, step) in graph.neighbours(&node) {
+            let next_cost = cost + step;
+            if search.cost(&next).is_some_and(|known| known <= next_cost) {
+                continue;
+            }
+
+            search.costs.insert(next.clone(), next_cost);
+            search.previous.insert(next.clone(), node.clone());
+            queue.push(Reverse((next_cost + heuristic(&next), nodes.len())));
+            nodes.push((next, next_cost));
+        }
+    }
+
+    search
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Roads between towns, with their lengths.
+    struct Roads(&'static [(char, char, u64)]);
+
+    impl Graph for Roads {
+        type Node = char;
+
+        fn neighbours(&self, town: &char) -> Vec<(char, u64)> {
+            self.0
+                .iter()
+                .filter(|(from, _, _)| from == town)
+                .map(|&(_, to, length)| (to, length))
+                .collect()
+        }
+    }
+
+    /// A to D is one road, but the long way round through B and C is shorter.
+    const ROADS: Roads = Roads(&[
+        ('A', 'D', 10),
+        ('A', 'B', 1),
+        ('B', 'C', 2),
+        ('C', 'D', 3),
+        ('D', 'E', 1),
+        ('F', 'E', 1),
+    ]);
+
+    #[test]
+    fn breadth_first() {
+        let search = bfs(&ROADS, ['A'], |&town| town == 'D');
+
+        assert_eq!(search.goal(), Some(&'D'));
+        assert_eq!(search.goal_cost(), Some(1));
+        assert_eq!(search.goal_path(), Some(vec!['A', 'D']));
+    }
+
+    #[test]
+    fn cheapest_first() {
+        let search = dijkstra(&ROADS, ['A'], |&town| town == 'D');
+
+        assert_eq!(search.goal_cost(), Some(6));
+        assert_eq!(search.goal_path(), Some(vec!['A', 'B', 'C', 'D']));
+
+        let astar = astar(
+            &ROADS,
+            ['A'],
+            |&town| town == 'D',
+            |&town| u64::from(town != 'D'),
+        );
+        assert_eq!(astar.goal_cost(), Some(6));
+    }
+
+    #[test]
+    fn everything_reachable() {
+        let search = dijkstra(&ROADS, ['A'], |_| false);
+
+        assert_eq!(search.goal(), None);
+        assert_eq!(search.cost(&'E'), Some(7));
+        assert_eq!(search.cost(&'F'), None);
+        assert_eq!(search.path_to(&'F'), None);
+        assert_eq!(search.costs().len(), 5);
+    }
+
+    #[test]
+    fn many_starts() {
+        let search = bfs(&ROADS, ['A', 'C'], |&town| town == 'D');
+        assert_eq!(search.goal_path(), Some(vec!['A', 'D']));
+
+        let search = dijkstra(&ROADS, ['A', 'C'], |&town| town == 'E');
+        assert_eq!(search.goal_path(), Some(vec!['C', 'D', 'E']));
+        assert_eq!(search.goal_cost(), Some(4));
+    }
+}
@@ -0,0 +1,213 @@
+use std::{fmt::Write, time::Duration};
+
+use crate::{export, runner::Outcome, visualize::Frame};
+
+/// The most frames of a day's animation embedded in a report, to keep the page a sensible size.
+const MAX_FRAMES: usize = 200;
+
+/// How fast embedded animations play.
+const FPS: u32 = 10;
+
+/// How a day went, for the report.
+#[derive(Debug)]
+pub struct Entry {
+    pub day: u8,
+    pub outcome: Outcome,
+    pub elapsed: Duration,
+    /// The animation of the solution, if it draws one.
+    pub frames: Vec<Frame>,
+}
+
+/// Writes a self-contained HTML page with the answers, timings and animations of each day.
+pub fn html(entries: &[Entry]) -> anyhow::Result<String> {
+    let solved = entries.iter().filter(|entry| entry.outcome.is_ok()).count();
+    let total: Duration = entries.iter().map(|entry| entry.elapsed).sum();
+
+    let mut page = String::from(
+        "<!DOCTYPE html>\n\
+         <html lang=\"en\">\n\
+         <head>\n\
+         <meta charset=\"utf-8\">\n\
+         <title>Advent of Code 2022</title>\n\
+         <style>\n\
+         body { background: #0f0f23; color: #cccccc; font-family: monospace; margin: 2em; }\n\
+         h1, h2 { color: #00cc00; }\n\
+         table { border-collapse: collapse; }\n\
+         th, td { border: 1px solid #333340; padding: 0.3em 0.8em; text-align: left; vertical-align: top; }\n\
+         pre { margin: 0; }\n\
+         .ok { color: #00cc00; }\n\
+         .failed { color: #ff5555; }\n\
+         img { image-rendering: pixelated; max-width: 100%; }\n\
+         </style>\n\
+         </head>\n\
+         <body>\n\
+         <h1>Advent of Code 2022</h1>\n",
+    );
+
+    writeln!(
+        page,
+        "<p>{solved} of {} days solved in {total:?}.</p>",
+        entries.len()
+    )?;
+
+    page.push_str("<table>\n<tr><th>Day</th><th>Status</th><th>Part 1</th><th>Part 2</th><th>Time</th></tr>\n");
+
+    for entry in entries {
+        let (status, class) = match &entry.outcome {
+            Outcome::Finished(Ok(_)) => ("solved".to_string(), "ok"),
+            outcome => (outcome.to_string(), "failed"),
+        };
+        let answer = |answer: Option<&String>| {
+            answer.map_or_else(
+                || "&mdash;".to_string(),
+                |answer| format!("<pre>{}</pre>", escape(answer)),
+            )
+        };
+        let day = if entry.frames.is_empty() {
+            entry.day.to_string()
+        } else {
+            format!("<a href=\"#day{0}\">{0}</a>", entry.day)
+        };
+        let (part1, part2) = match &entry.outcome {
+            Outcome::Finished(Ok(answers)) => (
+                answer(answers.part1.as_ref()),
+                answer(answers.part2.as_ref()),
+            ),
+            _ => (answer(None), answer(None)),
+        };
+
+        writeln!(
+            page,
+            "<tr><td>{day}</td><td class=\"{class}\">{}</td><td>{part1}</td><td>{part2}</td><td>{:?}</td></tr>",
+            escape(&status),
+            entry.elapsed,
+        )?;
+    }
+
+    page.push_str("</table>\n");
+
+    for entry in entries.iter().filter(|entry| !entry.frames.is_empty()) {
+        let mut gif = Vec::new();
+        export::gif(&mut gif, &sample(&entry.frames), FPS)?;
+
+        writeln!(
+            page,
+            "<h2 id=\"day{day}\">Day {day}</h2>\n<img alt=\"Animation of day {day}\" src=\"data:image/gif;base64,{}\">",
+            base64(&gif),
+            day = entry.day,
+        )?;
+    }
+
+    page.push_str("</body>\n</html>\n");
+
+    Ok(page)
+}
+
+/// Picks at most [`MAX_FRAMES`] frames spread evenly through `frames`, always ending on the last.
+fn sample(frames: &[Frame]) -> Vec<Frame> {
+    if frames.len() <= MAX_FRAMES {
+        return frames.to_vec();
+    }
+
+    (1..=MAX_FRAMES)
+        .map(|i| frames[i * frames.len() / MAX_FRAMES - 1].clone())
+        .collect()
+}
+
+/// Escapes the characters HTML gives a meaning to.
+fn escape(text: &str) -> String {
+    text.chars().fold(String::new(), |mut escaped, c| {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            c => escaped.push(c),
+        }
+        escaped
+    })
+}
+
+/// Encodes `bytes` as standard base64 with padding, for embedding images in the page.
+fn base64(bytes: &[u8]) -> String {
+    const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+    let mut encoded = String::with_capacity(bytes.len().div_ceil(3) * 4);
+
+    for chunk in bytes.chunks(3) {
+        let b = [
+            chunk[0],
+            *chunk.get(1).unwrap_or(&0),
+            *chunk.get(2).unwrap_or(&0),
+        ];
+        let n = u32::from(b[0]) << 16 | u32::from(b[1]) << 8 | u32::from(b[2]);
+
+        for i in 0..4 {
+            if i <= chunk.len() {
+                encoded.push(ALPHABET[(n >> (18 - 6 * i) & 0x3f) as usize] as char);
+            } else {
+                encoded.push('=');
+            }
+        }
+    }
+
+    encoded
+}
+
+#[cfg(test)]
+mod tests {
+    use anyhow::anyhow;
+
+    use super::*;
+    use crate::Answers;
+
+    #[test]
+    fn encode_base64() {
+        assert_eq!(base64(b""), "");
+        assert_eq!(base64(b"f"), "Zg==");
+        assert_eq!(base64(b"fo"), "Zm8=");
+        assert_eq!(base64(b"foo"), "Zm9v");
+        assert_eq!(base64(b"foobar"), "Zm9vYmFy");
+    }
+
+    #[test]
+    fn sample_frames() {
+        let frames: Vec<_> = (0..1000)
+            .map(|i| Frame::from_text(i.to_string(), ""))
+            .collect();
+
+        let sampled = sample(&frames);
+        assert_eq!(sampled.len(), MAX_FRAMES);
+        assert_eq!(sampled.last().unwrap().title, "999");
+        assert_eq!(sample(&frames[..3]).len(), 3);
+    }
+
+    #[test]
+    fn write_page() {
+        let entries = [
+            Entry {
+                day: 1,
+                outcome: Outcome::Finished(Ok(Answers::new(24000, "<b>"))),
+                elapsed: Duration::from_millis(3),
+                frames: vec![],
+            },
+            Entry {
+                day: 2,
+                outcome: Outcome::Finished(Err(anyhow!("bad input"))),
+                elapsed: Duration::ZERO,
+                frames: vec![Frame::from_text("frame", "#.#")],
+            },
+        ];
+
+        let page = html(&entries).unwrap();
+
+        assert!(page.contains("1 of 2 days solved"));
+        assert!(page.contains("<pre>24000</pre>"));
+        assert!(page.contains("<pre>&lt;b&gt;</pre>"));
+        assert!(page.contains("failed: bad input"));
+        assert!(page.contains("<h2 id=\"day2\">Day 2</h2>"));
+        assert!(page.contains("data:image/gif;base64,R0lGOD"));
+        assert!(page.contains("<td><a href=\"#day2\">2</a></td>"));
+        assert!(!page.contains("#day1"));
+    }
+}
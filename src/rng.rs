@@ -0,0 +1,103 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// A small, seedable random number generator (xorshift64*). Good enough for making up inputs, and
+/// the same seed always gives the same numbers, so runs can be repeated.
+#[derive(Debug, Clone)]
+pub struct Rng(u64);
+
+impl Rng {
+    /// Creates a generator from `seed`. Any seed is fine, including zero.
+    pub fn new(seed: u64) -> Rng {
+        // Mix the seed with splitmix64 so that small seeds do not start out mostly zero bits, and
+        // make sure the state is never zero, which xorshift can never leave
+        let mut z = seed.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^= z >> 31;
+
+        Rng(z.max(1))
+    }
+
+    /// Picks a seed from the clock, for when the caller does not care which one is used.
+    pub fn seed() -> u64 {
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |since| since.as_nanos() as u64)
+    }
+
+    /// Returns the next random number.
+    pub fn next_u64(&mut self) -> u64 {
+        self.0 ^= self.0 >> 12;
+        self.0 ^= self.0 << 25;
+        self.0 ^= self.0 >> 27;
+        self.0.wrapping_mul(0x2545_F491_4F6C_DD1D)
+    }
+
+    /// Returns a random number below `n`, or zero if `n` is zero.
+    pub fn below(&mut self, n: usize) -> usize {
+        if n == 0 {
+            return 0;
+        }
+
+        (self.next_u64() % n as u64) as usize
+    }
+
+    /// Returns a random number from `low` to `high` inclusive, or `low` if `high` is below it.
+    pub fn between(&mut self, low: usize, high: usize) -> usize {
+        low + self.below(high.saturating_sub(low).saturating_add(1))
+    }
+
+    /// Returns true with a chance of one in `n`.
+    pub fn one_in(&mut self, n: usize) -> bool {
+        self.below(n) == 0
+    }
+
+    /// Shuffles `items` into a random order.
+    pub fn shuffle<T>(&mut self, items: &mut [T]) {
+        for i in (1..items.len()).rev() {
+            items.swap(i, self.below(i + 1));
+        }
+    }
+
+    /// Returns a random item of `items`, or `None` if there are none.
+    pub fn choose<'a, T>(&mut self, items: &'a [T]) -> Option<&'a T> {
+        items.get(self.below(items.len()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn same_seed_same_numbers() {
+        let (mut a, mut b) = (Rng::new(0), Rng::new(0));
+        for _ in 0..100 {
+            assert_eq!(a.next_u64(), b.next_u64());
+        }
+
+        assert_ne!(Rng::new(1).next_u64(), Rng::new(2).next_u64());
+    }
+
+    #[test]
+    fn below() {
+        let mut rng = Rng::new(2022);
+        assert_eq!(rng.below(0), 0);
+        assert!((0..1000).all(|_| rng.below(7) < 7));
+        assert!((0..1000).all(|_| (3..=5).contains(&rng.between(3, 5))));
+        assert_eq!(rng.between(5, 3), 5);
+        assert_eq!(rng.choose::<u8>(&[]), None);
+        assert_eq!(rng.choose(&[3]), Some(&3));
+    }
+
+    #[test]
+    fn shuffle() {
+        let mut rng = Rng::new(2022);
+        let mut items: Vec<_> = (0..52).collect();
+        rng.shuffle(&mut items);
+
+        assert_ne!(items, (0..52).collect::<Vec<_>>());
+        items.sort_unstable();
+        assert_eq!(items, (0..52).collect::<Vec<_>>());
+    }
+}
@@ -0,0 +1,303 @@
+use std::{
+    cell::RefCell,
+    fmt,
+    io::{self, BufRead},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        mpsc::{self, RecvTimeoutError},
+        Arc,
+    },
+    thread,
+    time::{Duration, Instant},
+};
+
+use anyhow::anyhow;
+
+use crate::{visualize, Answers};
+
+/// The entry point of a day's solution.
+pub type Solution = fn(String) -> anyhow::Result<Answers>;
+
+/// The entry point of a day's solution that reads its input a bit at a time, so that inputs too big
+/// to hold in memory can be solved.
+pub type Stream = fn(&mut dyn BufRead) -> anyhow::Result<Answers>;
+
+/// One of the implementations of a day's solution.
+#[derive(Debug, Clone, Copy)]
+pub struct Variant {
+    /// The name to pick the implementation by.
+    pub name: &'static str,
+    /// The implementation.
+    pub solution: Solution,
+    /// The same implementation reading its input as a stream, if it can.
+    pub stream: Option<Stream>,
+}
+
+impl Variant {
+    /// The name of the implementation of days that have only one.
+    pub const DEFAULT: &'static str = "default";
+
+    /// Runs the implementation on `input`, streaming it if the input comes from a reader and the
+    /// implementation can read it that way.
+    pub fn solve(&self, input: Input) -> anyhow::Result<Answers> {
+        match (input, self.stream) {
+            (Input::Text(text), _) => (self.solution)(text),
+            (Input::Reader(mut reader), Some(stream)) => stream(&mut reader),
+            (Input::Reader(reader), None) => (self.solution)(io::read_to_string(reader)?),
+        }
+    }
+}
+
+/// The input to a solution.
+pub enum Input {
+    /// Input that has already been read in full.
+    Text(String),
+    /// Input still to be read, which is only read in full for solutions that cannot stream it.
+    Reader(Box<dyn BufRead + Send>),
+}
+
+impl From<String> for Input {
+    fn from(text: String) -> Input {
+        Input::Text(text)
+    }
+}
+
+thread_local! {
+    /// Set by the runner when the solution on this thread has run out of time.
+    static CANCELLED: RefCell<Option<Arc<AtomicBool>>> = const { RefCell::new(None) };
+}
+
+/// Has the solution running on this thread run out of time? Long-running solutions should check
+/// this now and then and give up early, since the runner cannot stop them otherwise.
+pub fn cancelled() -> bool {
+    CANCELLED.with(|cancelled| {
+        cancelled
+            .borrow()
+            .as_ref()
+            .is_some_and(|cancelled| cancelled.load(Ordering::Relaxed))
+    })
+}
+
+/// How a solution run ended.
+#[derive(Debug)]
+pub enum Outcome {
+    /// The solution returned, successfully or not.
+    Finished(anyhow::Result<Answers>),
+    /// The solution panicked.
+    Panicked,
+    /// The solution was still running when its time was up.
+    TimedOut(Duration),
+}
+
+impl Outcome {
+    /// Did the solution finish successfully?
+    pub fn is_ok(&self) -> bool {
+        matches!(self, Outcome::Finished(Ok(_)))
+    }
+}
+
+impl fmt::Display for Outcome {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Outcome::Finished(Ok(answers)) => write!(f, "{answers}"),
+            Outcome::Finished(Err(e)) => write!(f, "failed: {e}"),
+            Outcome::Panicked => write!(f, "panicked"),
+            Outcome::TimedOut(timeout) => write!(f, "timed out after {timeout:?}"),
+        }
+    }
+}
+
+/// Runs `variant` on its own thread, giving up on it after `timeout` if one is given. Returns how
+/// the run ended and how long it took.
+pub fn run(
+    name: &str,
+    variant: Variant,
+    input: Input,
+    timeout: Option<Duration>,
+) -> (Outcome, Duration) {
+    let (tx, rx) = mpsc::channel();
+    let cancel = Arc::new(AtomicBool::new(false));
+    let thread_cancel = Arc::clone(&cancel);
+
+    // Frames shown by the solution belong to this run's recording, even if it outlives the run
+    let recording = visualize::recording();
+
+    let start = Instant::now();
+
+    let spawned = thread::Builder::new()
+        .name(name.to_string())
+        .spawn(move || {
+            CANCELLED.with(|cancelled| *cancelled.borrow_mut() = Some(thread_cancel));
+            if let Some(recording) = recording {
+                visualize::resume(recording);
+            }
+            // The receiver is gone if the run timed out, in which case nobody wants the result
+            let _ = tx.send(variant.solve(input));
+        });
+
+    if let Err(e) = spawned {
+        let outcome = Outcome::Finished(Err(anyhow!("failed to start {name}: {e}")));
+        return (outcome, start.elapsed());
+    }
+
+    let result = match timeout {
+        Some(timeout) => rx.recv_timeout(timeout),
+        None => rx.recv().map_err(|_| RecvTimeoutError::Disconnected),
+    };
+
+    let outcome = match result {
+        Ok(result) => Outcome::Finished(result),
+        // The solution dropped the sender without sending, so it must have panicked
+        Err(RecvTimeoutError::Disconnected) => Outcome::Panicked,
+        Err(RecvTimeoutError::Timeout) => {
+            cancel.store(true, Ordering::Relaxed);
+            Outcome::TimedOut(timeout.unwrap_or_default())
+        }
+    };
+
+    (outcome, start.elapsed())
+}
+
+/// Parses a duration such as `30s`, `500ms` or `2m`. A bare number is taken as seconds.
+pub fn parse_duration(s: &str) -> Result<Duration, String> {
+    let s = s.trim();
+    let split = s.find(|c: char| !c.is_ascii_digit()).unwrap_or(s.len());
+    let (number, unit) = s.split_at(split);
+
+    let number: u64 = number
+        .parse()
+        .map_err(|_| format!("invalid duration {s:?}, expected e.g. 30s"))?;
+
+    match unit {
+        "ms" => Ok(Duration::from_millis(number)),
+        "" | "s" => Ok(Duration::from_secs(number)),
+        "m" => number
+            .checked_mul(60)
+            .map(Duration::from_secs)
+            .ok_or_else(|| format!("duration {s:?} is too long")),
+        _ => Err(format!(
+            "invalid duration unit {unit:?}, expected ms, s or m"
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use anyhow::bail;
+
+    use super::*;
+
+    /// A variant that can only read its input in full.
+    fn variant(solution: Solution) -> Variant {
+        Variant {
+            name: Variant::DEFAULT,
+            solution,
+            stream: None,
+        }
+    }
+
+    #[test]
+    fn run_finished() {
+        let empty = || Input::from(String::new());
+
+        let (outcome, _) = run("ok", variant(|_| Ok(Answers::new(1, 2))), empty(), None);
+        assert!(outcome.is_ok());
+        assert_eq!(outcome.to_string(), "part 1: 1, part 2: 2");
+
+        let (outcome, _) = run("err", variant(|_| bail!("bad input")), empty(), None);
+        assert_eq!(outcome.to_string(), "failed: bad input");
+
+        let (outcome, _) = run("panic", variant(|_| panic!("oops")), empty(), None);
+        assert!(matches!(outcome, Outcome::Panicked));
+    }
+
+    #[test]
+    fn streams_from_readers() {
+        let reader = || Input::Reader(Box::new("12\n".as_bytes()));
+        let whole = variant(|input| Ok(Answers::part1(format!("whole {}", input.trim()))));
+        let streaming = Variant {
+            stream: Some(|input| {
+                let mut line = String::new();
+                input.read_line(&mut line)?;
+                Ok(Answers::part1(format!("streamed {}", line.trim())))
+            }),
+            ..whole
+        };
+
+        assert_eq!(whole.solve(reader()).unwrap(), Answers::part1("whole 12"));
+        assert_eq!(
+            streaming.solve(reader()).unwrap(),
+            Answers::part1("streamed 12")
+        );
+        assert_eq!(
+            streaming.solve(Input::from("34".to_string())).unwrap(),
+            Answers::part1("whole 34")
+        );
+    }
+
+    #[test]
+    fn run_timed_out() {
+        let timeout = Duration::from_millis(50);
+
+        let (outcome, elapsed) = run(
+            "slow",
+            variant(|_| {
+                while !cancelled() {
+                    thread::sleep(Duration::from_millis(1));
+                }
+                Ok(Answers::default())
+            }),
+            Input::from(String::new()),
+            Some(timeout),
+        );
+
+        assert!(matches!(outcome, Outcome::TimedOut(t) if t == timeout));
+        assert!(elapsed >= timeout);
+        assert!(!cancelled());
+    }
+
+    #[test]
+    fn recordings_belong_to_their_run() {
+        struct Tick;
+        impl visualize::Visualize for Tick {
+            fn frame(&self) -> visualize::Frame {
+                visualize::Frame::from_text("tick", "x")
+            }
+        }
+
+        // Shows frames for a while after its run has given up on it
+        let slow = variant(|_| {
+            for _ in 0..100 {
+                visualize::show(&Tick);
+                thread::sleep(Duration::from_millis(2));
+            }
+            Ok(Answers::default())
+        });
+
+        visualize::record();
+        let (outcome, _) = run(
+            "slow",
+            slow,
+            Input::from(String::new()),
+            Some(Duration::from_millis(20)),
+        );
+        assert!(matches!(outcome, Outcome::TimedOut(_)));
+        assert!(!visualize::recorded().is_empty());
+
+        visualize::record();
+        thread::sleep(Duration::from_millis(50));
+        assert!(visualize::recorded().is_empty());
+    }
+
+    #[test]
+    fn test_parse_duration() {
+        assert_eq!(parse_duration("30s"), Ok(Duration::from_secs(30)));
+        assert_eq!(parse_duration("30"), Ok(Duration::from_secs(30)));
+        assert_eq!(parse_duration("250ms"), Ok(Duration::from_millis(250)));
+        assert_eq!(parse_duration("2m"), Ok(Duration::from_secs(120)));
+        assert!(parse_duration(&format!("{}m", u64::MAX)).is_err());
+        assert!(parse_duration("").is_err());
+        assert!(parse_duration("s").is_err());
+        assert!(parse_duration("10h").is_err());
+    }
+}
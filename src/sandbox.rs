@@ -0,0 +1,311 @@
+use std::{
+    env,
+    io::{self, BufReader, Read, Write},
+    process::{Command, ExitStatus, Stdio},
+    thread,
+    time::{Duration, Instant},
+};
+
+use anyhow::{anyhow, bail};
+
+use crate::{
+    explain,
+    runner::{Input, Outcome, Variant},
+    visualize, Answers,
+};
+
+/// Resource limits for a solution running in a child process.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Limits {
+    /// The most address space the child may use, in MiB.
+    pub memory: Option<u64>,
+    /// The most CPU time the child may use, in seconds.
+    pub cpu: Option<u64>,
+}
+
+impl Limits {
+    /// The memory limit, in MiB, of solutions sandboxed without one being given.
+    pub const DEFAULT_MEMORY: u64 = 1024;
+    /// The largest memory limit, in MiB, that still fits in bytes.
+    pub const MAX_MEMORY: u64 = u64::MAX / (1024 * 1024);
+    /// The largest CPU limit, in seconds, that leaves room for the hard limit a second later.
+    pub const MAX_CPU: u64 = u64::MAX - 1;
+}
+
+/// Runs the `variant` of the solution for `day` in a child process of this program, which applies
+/// `limits` to itself, reads `input` from its stdin and sends its answers back over its stdout. The
+/// child is killed if it is still running after `timeout`. Returns how the run ended and how long
+/// it took.
+pub fn run(
+    day: u8,
+    variant: &str,
+    input: Input,
+    limits: Limits,
+    timeout: Option<Duration>,
+) -> (Outcome, Duration) {
+    let start = Instant::now();
+    let outcome = match run_child(day, variant, input, limits, timeout, start) {
+        Ok(outcome) => outcome,
+        Err(e) => Outcome::Finished(Err(anyhow!("failed to run child process: {e}"))),
+    };
+
+    (outcome, start.elapsed())
+}
+
+/// Spawns the child process for `day` and waits for it to finish or run out of time.
+fn run_child(
+    day: u8,
+    variant: &str,
+    input: Input,
+    limits: Limits,
+    timeout: Option<Duration>,
+    start: Instant,
+) -> anyhow::Result<Outcome> {
+    let mut command = Command::new(env::current_exe()?);
+    command.args(["--day", &day.to_string(), "--variant", variant, "solve"]);
+
+    if let Some(memory) = limits.memory {
+        command.args(["--memory-limit", &memory.to_string()]);
+    }
+    if let Some(cpu) = limits.cpu {
+        command.args(["--cpu-limit", &cpu.to_string()]);
+    }
+    if explain::enabled() {
+        command.arg("--explain");
+    }
+    if let Some(fps) = visualize::fps() {
+        command.args(["--visualize", "--fps", &fps.to_string()]);
+    }
+
+    let mut child = command
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::inherit())
+        .spawn()?;
+
+    // Feed and drain the pipes on their own threads so that neither side blocks on a full pipe
+    let mut stdin = child
+        .stdin
+        .take()
+        .ok_or_else(|| anyhow!("no child stdin"))?;
+    thread::spawn(move || {
+        // Fails if the child dies before reading everything, which the exit status will explain
+        let _ = match input {
+            Input::Text(text) => stdin.write_all(text.as_bytes()),
+            Input::Reader(mut reader) => io::copy(&mut reader, &mut stdin).map(drop),
+        };
+    });
+
+    let mut stdout = child
+        .stdout
+        .take()
+        .ok_or_else(|| anyhow!("no child stdout"))?;
+    let reader = thread::spawn(move || {
+        let mut output = String::new();
+        stdout.read_to_string(&mut output).map(|_| output)
+    });
+
+    let status = loop {
+        if let Some(status) = child.try_wait()? {
+            break status;
+        }
+
+        if let Some(timeout) = timeout.filter(|&timeout| start.elapsed() >= timeout) {
+            child.kill()?;
+            child.wait()?;
+            return Ok(Outcome::TimedOut(timeout));
+        }
+
+        thread::sleep(Duration::from_millis(5));
+    };
+
+    let output = reader
+        .join()
+        .map_err(|_| anyhow!("failed to read child stdout"))??;
+
+    // Rust exits with 101 when the main thread panics
+    if status.code() == Some(101) {
+        return Ok(Outcome::Panicked);
+    }
+
+    if !status.success() {
+        return Ok(Outcome::Finished(Err(exit_error(status, limits))));
+    }
+
+    Ok(Outcome::Finished(decode(&output)?))
+}
+
+/// Explains why the child process exited unsuccessfully.
+fn exit_error(status: ExitStatus, limits: Limits) -> anyhow::Error {
+    #[cfg(unix)]
+    {
+        use std::os::unix::process::ExitStatusExt;
+
+        match (status.signal(), limits) {
+            (Some(libc::SIGXCPU), Limits { cpu: Some(cpu), .. }) => {
+                return anyhow!("exceeded the CPU limit of {cpu}s");
+            }
+            (
+                Some(signal @ (libc::SIGABRT | libc::SIGSEGV)),
+                Limits {
+                    memory: Some(mib), ..
+                },
+            ) => {
+                return anyhow!(
+                    "killed by signal {signal}, probably by exceeding the memory limit of {mib} MiB"
+                );
+            }
+            (Some(signal), _) => return anyhow!("killed by signal {signal}"),
+            (None, _) => {}
+        }
+    }
+
+    anyhow!("child process exited with {status}")
+}
+
+/// The child side of [`run`]: applies `limits`, runs `variant` on the input from stdin, streaming
+/// it if the variant can, and writes the result to stdout.
+pub fn solve(variant: Variant, limits: Limits) -> anyhow::Result<()> {
+    apply(limits)?;
+
+    let result = variant.solve(Input::Reader(Box::new(BufReader::new(io::stdin()))));
+
+    let mut stdout = io::stdout().lock();
+    stdout.write_all(encode(&result).as_bytes())?;
+    stdout.flush()?;
+
+    Ok(())
+}
+
+/// Applies resource limits to the current process.
+#[cfg(unix)]
+fn apply(limits: Limits) -> anyhow::Result<()> {
+    /// Sets the soft and hard limit of a resource.
+    macro_rules! set_rlimit {
+        ($resource:expr, $soft:expr, $hard:expr) => {{
+            let limit = libc::rlimit {
+                rlim_cur: $soft as libc::rlim_t,
+                rlim_max: $hard as libc::rlim_t,
+            };
+
+            // SAFETY: `limit` is a valid `rlimit` that outlives the call.
+            if unsafe { libc::setrlimit($resource, &limit) } != 0 {
+                bail!(
+                    "failed to set {}: {}",
+                    stringify!($resource),
+                    io::Error::last_os_error()
+                );
+            }
+        }};
+    }
+
+    if let Some(memory) = limits.memory {
+        let Some(bytes) = memory.checked_mul(1024 * 1024) else {
+            bail!("memory limit of {memory} MiB is too large");
+        };
+        set_rlimit!(libc::RLIMIT_AS, bytes, bytes);
+    }
+    if let Some(cpu) = limits.cpu {
+        // The soft limit sends SIGXCPU, which the parent reports, before the hard limit sends
+        // SIGKILL
+        let Some(hard) = cpu.checked_add(1) else {
+            bail!("CPU limit of {cpu}s is too large");
+        };
+        set_rlimit!(libc::RLIMIT_CPU, cpu, hard);
+    }
+
+    Ok(())
+}
+
+/// Applies resource limits to the current process.
+#[cfg(not(unix))]
+fn apply(limits: Limits) -> anyhow::Result<()> {
+    if limits != Limits::default() {
+        bail!("resource limits are only supported on unix");
+    }
+
+    Ok(())
+}
+
+/// Encodes the result of a solution for sending over a pipe. Answers are length-prefixed, since
+/// some of them span multiple lines.
+fn encode(result: &anyhow::Result<Answers>) -> String {
+    match result {
+        Ok(answers) => {
+            let mut encoded = String::from("ok\n");
+            for answer in [&answers.part1, &answers.part2] {
+                match answer {
+                    Some(answer) => encoded.push_str(&format!("{}\n{answer}\n", answer.len())),
+                    None => encoded.push_str("-\n"),
+                }
+            }
+            encoded
+        }
+        Err(e) => format!("error\n{e}"),
+    }
+}
+
+/// Decodes the result of a solution sent over a pipe by [`encode`].
+fn decode(encoded: &str) -> anyhow::Result<anyhow::Result<Answers>> {
+    let Some((status, mut rest)) = encoded.split_once('\n') else {
+        bail!("child process sent no result");
+    };
+
+    match status {
+        "ok" => {}
+        "error" => return Ok(Err(anyhow!("{rest}"))),
+        _ => bail!("child process sent unknown status {status:?}"),
+    }
+
+    let mut answers = [None, None];
+
+    for answer in &mut answers {
+        let (len, after) = rest
+            .split_once('\n')
+            .ok_or_else(|| anyhow!("child process sent truncated answers"))?;
+
+        if len == "-" {
+            rest = after;
+            continue;
+        }
+
+        let len: usize = len.parse()?;
+        let value = after
+            .get(..len)
+            .ok_or_else(|| anyhow!("child process sent truncated answers"))?;
+
+        *answer = Some(value.to_string());
+        rest = after.get(len + 1..).unwrap_or_default();
+    }
+
+    let [part1, part2] = answers;
+
+    Ok(Ok(Answers { part1, part2 }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn encode_decode() {
+        let answers = Answers::new(24000, "###.\n#..#\n");
+        let decoded = decode(&encode(&Ok(answers.clone()))).unwrap().unwrap();
+        assert_eq!(decoded, answers);
+
+        let answers = Answers::part1("CMZ");
+        let decoded = decode(&encode(&Ok(answers.clone()))).unwrap().unwrap();
+        assert_eq!(decoded, answers);
+
+        let decoded = decode(&encode(&Err(anyhow!("bad input")))).unwrap();
+        assert_eq!(decoded.unwrap_err().to_string(), "bad input");
+    }
+
+    #[test]
+    fn decode_invalid() {
+        assert!(decode("").is_err());
+        assert!(decode("maybe\n").is_err());
+        assert!(decode("ok\n10\nshort\n-\n").is_err());
+        assert!(decode("ok\n-\n").is_err());
+    }
+}
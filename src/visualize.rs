@@ -0,0 +1,365 @@
+use std::{
+    cell::RefCell,
+    fmt,
+    io::{self, Write},
+    sync::{
+        atomic::{AtomicBool, AtomicU32, Ordering},
+        Arc, Mutex, PoisonError,
+    },
+    thread,
+    time::Duration,
+};
+
+/// Frames per second to play at, set by `--visualize` and `--fps`. Zero when not visualizing.
+static FPS: AtomicU32 = AtomicU32::new(0);
+
+/// Whether the terminal has been cleared for the first frame yet.
+static STARTED: AtomicBool = AtomicBool::new(false);
+
+/// The most frames [`record`] keeps, so a long or runaway solution cannot fill up memory.
+pub const MAX_RECORDED: usize = 5000;
+
+/// The frames shown during one run since [`record`], for exporting once its solution finishes.
+/// It is shared with the thread the solution runs on, so a solution left running after its run
+/// has given up on it keeps adding to its own recording and not the next run's.
+#[derive(Debug, Clone, Default)]
+pub struct Recording(Arc<Mutex<Vec<Frame>>>);
+
+thread_local! {
+    /// The frames so far, while [`capture`] is collecting them on this thread.
+    static CAPTURED: RefCell<Option<Vec<Frame>>> = const { RefCell::new(None) };
+
+    /// The recording that frames shown on this thread go to, if there is one.
+    static RECORDING: RefCell<Option<Recording>> = const { RefCell::new(None) };
+}
+
+/// Something that can be drawn as a frame of an animation, e.g. the state of a puzzle after
+/// each step of a solution.
+pub trait Visualize {
+    /// Draws the current state.
+    fn frame(&self) -> Frame;
+}
+
+/// The colors a cell can be drawn in, besides the terminal's own.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Color {
+    Red,
+    Green,
+    Yellow,
+    Blue,
+    Magenta,
+    Cyan,
+    White,
+    Grey,
+    /// Any color, for shading things like heights. Needs a terminal with true color.
+    Rgb(u8, u8, u8),
+}
+
+impl Color {
+    /// The ANSI escape code that draws in this color.
+    fn ansi(self) -> String {
+        match self {
+            Color::Red => "\x1b[31m".into(),
+            Color::Green => "\x1b[32m".into(),
+            Color::Yellow => "\x1b[33m".into(),
+            Color::Blue => "\x1b[34m".into(),
+            Color::Magenta => "\x1b[35m".into(),
+            Color::Cyan => "\x1b[36m".into(),
+            Color::White => "\x1b[97m".into(),
+            Color::Grey => "\x1b[90m".into(),
+            Color::Rgb(r, g, b) => format!("\x1b[38;2;{r};{g};{b}m"),
+        }
+    }
+
+    /// The red, green and blue parts of the color, roughly as a terminal shows them.
+    pub fn rgb(self) -> [u8; 3] {
+        match self {
+            Color::Red => [205, 49, 49],
+            Color::Green => [13, 188, 121],
+            Color::Yellow => [229, 229, 16],
+            Color::Blue => [36, 114, 200],
+            Color::Magenta => [188, 63, 188],
+            Color::Cyan => [17, 168, 205],
+            Color::White => [255, 255, 255],
+            Color::Grey => [102, 102, 102],
+            Color::Rgb(r, g, b) => [r, g, b],
+        }
+    }
+
+    /// Shades `value` out of `max` from dark to light green, e.g. for heights.
+    pub fn shade(value: u32, max: u32) -> Color {
+        let light = (value.min(max) * 200 / max.max(1)) as u8;
+        Color::Rgb(light / 2, 55 + light, light / 2)
+    }
+}
+
+/// A character on a frame, drawn in the terminal's color if it has none.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Cell {
+    pub glyph: char,
+    pub color: Option<Color>,
+}
+
+impl Cell {
+    /// An empty cell.
+    pub const BLANK: Cell = Cell {
+        glyph: ' ',
+        color: None,
+    };
+}
+
+/// A grid of characters with a title, drawn as one step of an animation.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Frame {
+    /// Shown above the grid, e.g. the step the frame shows.
+    pub title: String,
+    /// The rows of the grid, top first. Rows may be of different lengths.
+    cells: Vec<Vec<Cell>>,
+}
+
+impl Frame {
+    /// Creates a blank frame `width` cells wide and `height` cells high.
+    pub fn new(title: impl Into<String>, width: usize, height: usize) -> Frame {
+        Frame {
+            title: title.into(),
+            cells: vec![vec![Cell::BLANK; width]; height],
+        }
+    }
+
+    /// Creates a frame of `text` without any colors, one row per line.
+    pub fn from_text(title: impl Into<String>, text: &str) -> Frame {
+        Frame {
+            title: title.into(),
+            cells: text
+                .lines()
+                .map(|line| {
+                    line.chars()
+                        .map(|glyph| Cell { glyph, color: None })
+                        .collect()
+                })
+                .collect(),
+        }
+    }
+
+    /// The length of the longest row.
+    pub fn width(&self) -> usize {
+        self.cells.iter().map(Vec::len).max().unwrap_or_default()
+    }
+
+    /// The number of rows.
+    pub fn height(&self) -> usize {
+        self.cells.len()
+    }
+
+    /// The rows of the frame, top first.
+    pub fn rows(&self) -> &[Vec<Cell>] {
+        &self.cells
+    }
+
+    /// Gets the cell in column `x` of row `y`, if there is one.
+    pub fn get(&self, x: usize, y: usize) -> Option<Cell> {
+        self.cells.get(y).and_then(|row| row.get(x)).copied()
+    }
+
+    /// Puts `glyph` in column `x` of row `y`. Does nothing outside the frame.
+    pub fn set(&mut self, x: usize, y: usize, glyph: char, color: Option<Color>) {
+        if let Some(cell) = self.cells.get_mut(y).and_then(|row| row.get_mut(x)) {
+            *cell = Cell { glyph, color };
+        }
+    }
+
+    /// Colors the cell in column `x` of row `y`, keeping its glyph. Does nothing outside the frame.
+    pub fn paint(&mut self, x: usize, y: usize, color: Color) {
+        if let Some(cell) = self.cells.get_mut(y).and_then(|row| row.get_mut(x)) {
+            cell.color = Some(color);
+        }
+    }
+
+    /// Draws the frame with ANSI escape codes for its colors, clearing what was on each line.
+    pub fn ansi(&self) -> String {
+        let mut drawing = format!("{}\x1b[K\n", self.title);
+
+        for row in &self.cells {
+            let mut color = None;
+            for cell in row {
+                if cell.color != color {
+                    drawing.push_str(&cell.color.map_or("\x1b[0m".into(), Color::ansi));
+                    color = cell.color;
+                }
+                drawing.push(cell.glyph);
+            }
+            if color.is_some() {
+                drawing.push_str("\x1b[0m");
+            }
+            drawing.push_str("\x1b[K\n");
+        }
+
+        drawing
+    }
+}
+
+impl fmt::Display for Frame {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "{}", self.title)?;
+        for row in &self.cells {
+            let line: String = row.iter().map(|cell| cell.glyph).collect();
+            writeln!(f, "{}", line.trim_end())?;
+        }
+        Ok(())
+    }
+}
+
+/// Turns visualizing on for every thread, playing `fps` frames per second, or off if `None`.
+pub fn enable(fps: Option<u32>) {
+    FPS.store(fps.unwrap_or_default(), Ordering::Relaxed);
+}
+
+/// The frames per second animations play at, if visualizing is on.
+pub fn fps() -> Option<u32> {
+    Some(FPS.load(Ordering::Relaxed)).filter(|&fps| fps > 0)
+}
+
+/// Is visualizing on? Solutions can check this before doing extra work for their frames.
+pub fn enabled() -> bool {
+    fps().is_some()
+        || RECORDING.with(|recording| recording.borrow().is_some())
+        || CAPTURED.with(|captured| captured.borrow().is_some())
+}
+
+/// Starts recording the frames shown on this thread, and on threads that [`resume`] its
+/// recording, as well as playing them if visualizing. Only the first [`MAX_RECORDED`] frames are
+/// kept.
+pub fn record() {
+    resume(Recording::default());
+}
+
+/// Stops recording on this thread, returning the frames shown since [`record`].
+pub fn recorded() -> Vec<Frame> {
+    RECORDING
+        .with(|recording| recording.take())
+        .map_or_else(Vec::new, |recording| {
+            // Still good after a solution panics while holding it
+            let mut frames = recording.0.lock().unwrap_or_else(PoisonError::into_inner);
+            std::mem::take(&mut *frames)
+        })
+}
+
+/// The recording that frames shown on this thread go to, to carry it on on another thread.
+pub fn recording() -> Option<Recording> {
+    RECORDING.with(|recording| recording.borrow().clone())
+}
+
+/// Sends the frames shown on this thread to `recording` from now on.
+pub fn resume(recording: Recording) {
+    RECORDING.with(|current| current.replace(Some(recording)));
+}
+
+/// Adds a frame of `thing` to the animation if visualizing is on. Frames are played on stderr,
+/// keeping stdout free for answers.
+pub fn show(thing: &impl Visualize) {
+    if !enabled() {
+        return;
+    }
+
+    let frame = thing.frame();
+
+    let frame = CAPTURED.with(|captured| match captured.borrow_mut().as_mut() {
+        Some(frames) => {
+            frames.push(frame);
+            None
+        }
+        None => Some(frame),
+    });
+
+    let Some(frame) = frame else {
+        return;
+    };
+
+    if fps().is_some() {
+        play(&frame);
+    }
+    RECORDING.with(|recording| {
+        if let Some(recording) = recording.borrow().as_ref() {
+            let mut frames = recording.0.lock().unwrap_or_else(PoisonError::into_inner);
+            if frames.len() < MAX_RECORDED {
+                frames.push(frame);
+            }
+        }
+    });
+}
+
+/// Draws `frame` over the last one in the terminal, then waits until the next frame is due.
+fn play(frame: &Frame) {
+    // Clear the screen once, then draw each frame from the top left over the last one
+    let clear = if STARTED.swap(true, Ordering::Relaxed) {
+        ""
+    } else {
+        "\x1b[2J"
+    };
+
+    let mut stderr = io::stderr().lock();
+    // Losing a frame to a closed terminal is no reason to stop the solution
+    let _ = write!(stderr, "{clear}\x1b[H{}\x1b[J", frame.ansi());
+    let _ = stderr.flush();
+    drop(stderr);
+
+    if let Some(fps) = fps() {
+        thread::sleep(Duration::from_secs(1) / fps);
+    }
+}
+
+/// Runs `f` with visualizing on for this thread, collecting the frames instead of playing them.
+pub fn capture<T>(f: impl FnOnce() -> T) -> (T, Vec<Frame>) {
+    let outer = CAPTURED.with(|captured| captured.borrow_mut().replace(Vec::new()));
+    let result = f();
+    let frames = CAPTURED.with(|captured| captured.replace(outer));
+
+    (result, frames.unwrap_or_default())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Counter(usize);
+
+    impl Visualize for Counter {
+        fn frame(&self) -> Frame {
+            let mut frame = Frame::new(format!("count {}", self.0), 3, 1);
+            frame.set(self.0, 0, '#', Some(Color::Red));
+            frame
+        }
+    }
+
+    #[test]
+    fn capture_frames() {
+        assert!(!enabled());
+
+        let (answer, frames) = capture(|| {
+            assert!(enabled());
+            for i in 0..3 {
+                show(&Counter(i));
+            }
+            42
+        });
+
+        assert_eq!(answer, 42);
+        assert_eq!(frames.len(), 3);
+        assert_eq!(frames[1].to_string(), "count 1\n #\n");
+        assert!(!enabled());
+    }
+
+    #[test]
+    fn draw_colors() {
+        let mut frame = Frame::from_text("title", "ab\ncd");
+        frame.paint(1, 0, Color::Green);
+        frame.set(5, 5, 'x', None);
+
+        assert_eq!(frame.width(), 2);
+        assert_eq!(frame.height(), 2);
+        assert_eq!(
+            frame.ansi(),
+            "title\x1b[K\na\x1b[32mb\x1b[0m\x1b[K\ncd\x1b[K\n"
+        );
+    }
+}
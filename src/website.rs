@@ -0,0 +1,229 @@
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use anyhow::{anyhow, bail};
+use reqwest::{Client, Response, StatusCode, Url};
+
+/// The year of the Advent of Code event.
+pub const YEAR: u16 = 2022;
+
+/// The number of days in the Advent of Code event.
+pub const DAYS: u8 = 25;
+
+/// Identifies requests from this program to the website's maintainers, as they ask automated tools
+/// to do.
+const USER_AGENT: &str = "github.com/alexpcook/advent-of-code-2022";
+
+/// Returns when the challenge for `day` unlocks, which is midnight US Eastern Time (UTC-5).
+pub fn unlocks_at(day: u8) -> SystemTime {
+    const SECONDS_PER_DAY: u64 = 24 * 60 * 60;
+    const EASTERN_TIME_OFFSET: u64 = 5 * 60 * 60;
+
+    let days = days_since_epoch(YEAR.into(), 12, day.into());
+
+    UNIX_EPOCH + Duration::from_secs(days * SECONDS_PER_DAY + EASTERN_TIME_OFFSET)
+}
+
+/// Returns the days whose challenges have unlocked by `now`.
+pub fn unlocked_days(now: SystemTime) -> impl Iterator<Item = u8> {
+    (1..=DAYS).take_while(move |&day| unlocks_at(day) <= now)
+}
+
+/// Counts the days from 1970-01-01 to the given date in the proleptic Gregorian calendar.
+fn days_since_epoch(year: u64, month: u64, day: u64) -> u64 {
+    // Treat January and February as the end of the previous year, so leap days come last
+    let (year, month) = if month <= 2 {
+        (year - 1, month + 9)
+    } else {
+        (year, month - 3)
+    };
+
+    let era = year / 400;
+    let year_of_era = year % 400;
+    let day_of_year = (153 * month + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+
+    // 719468 days separate 0000-03-01 from 1970-01-01
+    era * 146097 + day_of_era - 719468
+}
+
+/// A client for the Advent of Code website, authenticated with a session cookie.
+#[derive(Debug)]
+pub struct Website {
+    client: Client,
+    base_url: Url,
+    session: String,
+    timeout: Duration,
+}
+
+impl Website {
+    /// The base URL of the real Advent of Code website.
+    pub const BASE_URL: &'static str = "https://adventofcode.com";
+
+    /// How long to wait for the website to respond by default.
+    pub const TIMEOUT: Duration = Duration::from_secs(2);
+
+    /// Creates a client for the website at `base_url` using the `session` cookie.
+    pub fn new(base_url: &str, session: impl Into<String>) -> anyhow::Result<Website> {
+        Ok(Website {
+            client: Client::builder().user_agent(USER_AGENT).build()?,
+            base_url: Url::parse(base_url)?,
+            session: session.into(),
+            timeout: Self::TIMEOUT,
+        })
+    }
+
+    /// Sets how long to wait for the website to respond.
+    pub fn with_timeout(mut self, timeout: Duration) -> Website {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Gets the input for a challenge.
+    pub async fn input(&self, day: u8) -> anyhow::Result<String> {
+        let response = self.get(&format!("{YEAR}/day/{day}/input")).await?;
+
+        match response.status() {
+            StatusCode::OK => {
+                let input = response.text().await?;
+                check_input(&input)?;
+                Ok(input)
+            }
+            // The website asks you to log in when the session cookie is missing or expired
+            StatusCode::BAD_REQUEST => {
+                bail!("website did not accept the session cookie, please log in again")
+            }
+            StatusCode::NOT_FOUND => bail!("day {day} is not unlocked yet"),
+            status => bail!("got {status} status code getting input from website"),
+        }
+    }
+
+    /// Returns the name of the user that the session cookie belongs to.
+    pub async fn whoami(&self) -> anyhow::Result<String> {
+        let response = self.get(&YEAR.to_string()).await?;
+
+        let Ok(response) = response.error_for_status() else {
+            bail!("got non-200 status code getting user from website");
+        };
+
+        let page = response.text().await?;
+
+        user_name(&page).ok_or_else(|| anyhow!("session cookie was not accepted by the website"))
+    }
+
+    /// Sends a GET request for `path` with the session cookie.
+    async fn get(&self, path: &str) -> anyhow::Result<Response> {
+        let response = self
+            .client
+            .get(self.base_url.join(path)?)
+            .timeout(self.timeout)
+            .header("cookie", format!("session={}", self.session))
+            .send()
+            .await?;
+
+        Ok(response)
+    }
+}
+
+/// Checks that a response body looks like puzzle input rather than a message or page from the
+/// website, which must not end up cached as input.
+fn check_input(body: &str) -> anyhow::Result<()> {
+    if body.trim().is_empty() {
+        bail!("website returned empty input");
+    }
+
+    if body.starts_with("Please don't repeatedly request this endpoint") {
+        bail!("website asked not to request input before it unlocks");
+    }
+
+    if body.trim_start().starts_with('<') || body.to_lowercase().contains("please log in") {
+        bail!("website returned a page instead of input, the session cookie may have expired");
+    }
+
+    Ok(())
+}
+
+/// Finds the name of the logged in user in a page from the website. Returns `None` if nobody is
+/// logged in.
+fn user_name(page: &str) -> Option<String> {
+    const USER_DIV: &str = r#"<div class="user">"#;
+
+    let (_, rest) = page.split_once(USER_DIV)?;
+    let name = rest.split('<').next()?.trim();
+
+    (!name.is_empty()).then(|| name.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_days_since_epoch() {
+        assert_eq!(days_since_epoch(1970, 1, 1), 0);
+        assert_eq!(days_since_epoch(2000, 3, 1), 11017);
+        assert_eq!(days_since_epoch(2022, 12, 1), 19327);
+    }
+
+    #[test]
+    fn test_unlocks_at() {
+        // 2022-12-01T05:00:00Z
+        assert_eq!(unlocks_at(1), UNIX_EPOCH + Duration::from_secs(1669870800));
+        // 2022-12-25T05:00:00Z
+        assert_eq!(unlocks_at(25), UNIX_EPOCH + Duration::from_secs(1671944400));
+    }
+
+    #[test]
+    fn test_unlocked_days() {
+        let now = unlocks_at(3) + Duration::from_secs(1);
+        assert_eq!(unlocked_days(now).collect::<Vec<_>>(), [1, 2, 3]);
+
+        let now = unlocks_at(1) - Duration::from_secs(1);
+        assert_eq!(unlocked_days(now).count(), 0);
+
+        let now = unlocks_at(25) + Duration::from_secs(365 * 24 * 60 * 60);
+        assert_eq!(unlocked_days(now).count(), 25);
+    }
+
+    #[test]
+    fn check_input_accepts_input() {
+        assert!(check_input("1000\n2000\n\n3000\n").is_ok());
+        assert!(check_input("    [D]    \n[N] [C]    \n").is_ok());
+        assert!(check_input("$ cd /\n$ ls\ndir a\n").is_ok());
+    }
+
+    #[test]
+    fn check_input_rejects_messages() {
+        assert!(check_input("").is_err());
+        assert!(check_input("\n").is_err());
+        assert!(check_input(
+            "Please don't repeatedly request this endpoint before it unlocks! The calendar..."
+        )
+        .is_err());
+        assert!(check_input(
+            "Puzzle inputs differ by user.  Please log in to get your puzzle input.\n"
+        )
+        .is_err());
+        assert!(check_input("<!DOCTYPE html>\n<html lang=\"en-us\">").is_err());
+    }
+
+    #[test]
+    fn user_name_logged_in() {
+        let page = r#"<header><div class="user">alexpcook <span class="star-count">24*</span></div></header>"#;
+        assert_eq!(user_name(page), Some("alexpcook".to_string()));
+
+        let page = r#"<div class="user">(anonymous user #123456)</div>"#;
+        assert_eq!(
+            user_name(page),
+            Some("(anonymous user #123456)".to_string())
+        );
+    }
+
+    #[test]
+    fn user_name_logged_out() {
+        let page = r#"<header><a href="/2022/auth/login">[Log In]</a></header>"#;
+        assert_eq!(user_name(page), None);
+
+        let page = r#"<div class="user"> <span class="star-count">24*</span></div>"#;
+        assert_eq!(user_name(page), None);
+    }
+}
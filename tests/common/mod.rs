@@ -0,0 +1,156 @@
+//! A local stand-in for the Advent of Code website.
+
+#![allow(dead_code)]
+
+use std::{
+    collections::HashMap,
+    env, fs,
+    net::SocketAddr,
+    path::PathBuf,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::{TcpListener, TcpStream},
+};
+
+/// The session cookie the stand-in accepts.
+pub const SESSION: &str = "53616c7465645f5f";
+
+/// The user the accepted session cookie belongs to.
+pub const USER: &str = "alexpcook";
+
+/// What the website says when asked for input without a valid session cookie.
+pub const LOG_IN: &str = "Puzzle inputs differ by user.  Please log in to get your puzzle input.\n";
+
+/// What the website says when asked for input of a day that is not unlocked yet.
+pub const NOT_UNLOCKED: &str = "Please don't repeatedly request this endpoint before it unlocks! \
+    The calendar countdown is synchronized with the server time; the link will be enabled on the \
+    calendar the instant this puzzle becomes available.\n";
+
+/// Creates an empty directory for a test to cache inputs in.
+pub fn input_dir(test: &str) -> PathBuf {
+    let dir = env::temp_dir().join(format!("aoc-{test}-{}", std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+/// A stand-in website listening on a local port.
+pub struct StandIn {
+    addr: SocketAddr,
+    state: Arc<State>,
+}
+
+/// What the stand-in serves and what it has been asked for.
+#[derive(Default)]
+struct State {
+    /// Inputs of the unlocked days.
+    inputs: Mutex<HashMap<u8, String>>,
+    /// How long to wait before responding.
+    delay: Mutex<Duration>,
+    /// The paths requested so far.
+    requests: Mutex<Vec<String>>,
+}
+
+impl StandIn {
+    /// Starts the stand-in on a free local port.
+    pub async fn start() -> StandIn {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let state = Arc::new(State::default());
+
+        let shared = Arc::clone(&state);
+        tokio::spawn(async move {
+            while let Ok((stream, _)) = listener.accept().await {
+                tokio::spawn(serve(stream, Arc::clone(&shared)));
+            }
+        });
+
+        StandIn { addr, state }
+    }
+
+    /// The base URL of the stand-in.
+    pub fn url(&self) -> String {
+        format!("http://{}", self.addr)
+    }
+
+    /// Unlocks `day` with the given puzzle input.
+    pub fn unlock(&self, day: u8, input: &str) {
+        self.state
+            .inputs
+            .lock()
+            .unwrap()
+            .insert(day, input.to_string());
+    }
+
+    /// Makes the stand-in wait for `delay` before every response.
+    pub fn delay(&self, delay: Duration) {
+        *self.state.delay.lock().unwrap() = delay;
+    }
+
+    /// The paths requested so far.
+    pub fn requests(&self) -> Vec<String> {
+        self.state.requests.lock().unwrap().clone()
+    }
+}
+
+/// Answers a single request like the real website would.
+async fn serve(mut stream: TcpStream, state: Arc<State>) {
+    let mut buf = Vec::new();
+    let mut chunk = [0; 1024];
+
+    while !buf.windows(4).any(|w| w == b"\r\n\r\n") {
+        match stream.read(&mut chunk).await {
+            Ok(0) | Err(_) => return,
+            Ok(n) => buf.extend_from_slice(&chunk[..n]),
+        }
+    }
+
+    let request = String::from_utf8_lossy(&buf);
+    let path = request
+        .split_whitespace()
+        .nth(1)
+        .unwrap_or_default()
+        .to_string();
+    let logged_in = request
+        .lines()
+        .any(|line| line.eq_ignore_ascii_case(&format!("cookie: session={SESSION}")));
+
+    state.requests.lock().unwrap().push(path.clone());
+
+    let input_day = path
+        .strip_prefix("/2022/day/")
+        .and_then(|rest| rest.strip_suffix("/input"))
+        .and_then(|day| day.parse::<u8>().ok());
+
+    let (status, body) = match (path.as_str(), input_day) {
+        ("/2022", _) if logged_in => (
+            "200 OK",
+            format!(
+                r#"<header><div class="user">{USER} <span class="star-count">24*</span></div></header>"#
+            ),
+        ),
+        ("/2022", _) => (
+            "200 OK",
+            r#"<header><a href="/2022/auth/login">[Log In]</a></header>"#.to_string(),
+        ),
+        (_, Some(_)) if !logged_in => ("400 Bad Request", LOG_IN.to_string()),
+        (_, Some(day)) => match state.inputs.lock().unwrap().get(&day) {
+            Some(input) => ("200 OK", input.clone()),
+            None => ("404 Not Found", NOT_UNLOCKED.to_string()),
+        },
+        _ => ("404 Not Found", "404 Not Found\n".to_string()),
+    };
+
+    let delay = *state.delay.lock().unwrap();
+    tokio::time::sleep(delay).await;
+
+    let response = format!(
+        "HTTP/1.1 {status}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+        body.len()
+    );
+    let _ = stream.write_all(response.as_bytes()).await;
+}
@@ -0,0 +1,228 @@
+mod common;
+
+use std::{
+    fs,
+    time::{Duration, Instant},
+};
+
+use advent_of_code_2022::{
+    fetch_all, input,
+    website::{self, Website},
+};
+
+use common::StandIn;
+
+#[tokio::test]
+async fn input_from_website_is_cached() {
+    let stand_in = StandIn::start().await;
+    stand_in.unlock(1, "1000\n2000\n");
+    let website = Website::new(&stand_in.url(), common::SESSION).unwrap();
+    let dir = common::input_dir("cached");
+
+    let got = input(1, &dir, Some(&website), false).await.unwrap();
+    assert_eq!(got, "1000\n2000\n");
+    assert_eq!(fs::read_to_string(dir.join("day_1.txt")).unwrap(), got);
+
+    // The second time around the cache is used instead of the website
+    let got = input(1, &dir, Some(&website), false).await.unwrap();
+    assert_eq!(got, "1000\n2000\n");
+    assert_eq!(stand_in.requests(), ["/2022/day/1/input"]);
+
+    fs::remove_dir_all(dir).unwrap();
+}
+
+#[tokio::test]
+async fn input_without_session_needs_cache() {
+    let dir = common::input_dir("no-session");
+
+    let err = input(1, &dir, None, false).await.unwrap_err();
+    assert!(err.to_string().contains("no session cookie"), "{err}");
+
+    fs::write(dir.join("day_1.txt"), "cached").unwrap();
+    assert_eq!(input(1, &dir, None, false).await.unwrap(), "cached");
+
+    fs::remove_dir_all(dir).unwrap();
+}
+
+#[tokio::test]
+async fn input_please_log_in() {
+    let stand_in = StandIn::start().await;
+    stand_in.unlock(1, "1000\n");
+    let website = Website::new(&stand_in.url(), "expired").unwrap();
+    let dir = common::input_dir("log-in");
+
+    let err = input(1, &dir, Some(&website), false).await.unwrap_err();
+    assert!(err.to_string().contains("log in"), "{err}");
+    assert!(!dir.join("day_1.txt").exists());
+
+    fs::remove_dir_all(dir).unwrap();
+}
+
+#[tokio::test]
+async fn input_not_yet_unlocked() {
+    let stand_in = StandIn::start().await;
+    let website = Website::new(&stand_in.url(), common::SESSION).unwrap();
+    let dir = common::input_dir("not-unlocked");
+
+    let err = input(25, &dir, Some(&website), false).await.unwrap_err();
+    assert!(err.to_string().contains("not unlocked"), "{err}");
+    assert!(!dir.join("day_25.txt").exists());
+
+    fs::remove_dir_all(dir).unwrap();
+}
+
+#[tokio::test]
+async fn input_timeout() {
+    let stand_in = StandIn::start().await;
+    stand_in.unlock(1, "1000\n");
+    stand_in.delay(Duration::from_secs(5));
+    let website = Website::new(&stand_in.url(), common::SESSION)
+        .unwrap()
+        .with_timeout(Duration::from_millis(100));
+    let dir = common::input_dir("timeout");
+
+    let err = input(1, &dir, Some(&website), false).await.unwrap_err();
+    assert!(err.to_string().contains("timed out"), "{err}");
+    assert!(!dir.join("day_1.txt").exists());
+
+    fs::remove_dir_all(dir).unwrap();
+}
+
+#[tokio::test]
+async fn input_cache_directory_is_created() {
+    let stand_in = StandIn::start().await;
+    stand_in.unlock(1, "1000\n");
+    let website = Website::new(&stand_in.url(), common::SESSION).unwrap();
+    let dir = common::input_dir("create-dir");
+    let input_dir = dir.join("input");
+
+    input(1, &input_dir, Some(&website), false).await.unwrap();
+    assert_eq!(
+        fs::read_to_string(input_dir.join("day_1.txt")).unwrap(),
+        "1000\n"
+    );
+
+    fs::remove_dir_all(dir).unwrap();
+}
+
+#[tokio::test]
+async fn input_cache_write_failure() {
+    let stand_in = StandIn::start().await;
+    stand_in.unlock(1, "1000\n");
+    let website = Website::new(&stand_in.url(), common::SESSION).unwrap();
+    let dir = common::input_dir("write-failure");
+
+    // A non-empty directory where the input file should be cannot be replaced
+    fs::create_dir_all(dir.join("day_1.txt").join("blocker")).unwrap();
+
+    let err = input(1, &dir, Some(&website), true).await.unwrap_err();
+    assert!(err.to_string().contains("failed to cache input"), "{err}");
+    assert_eq!(stand_in.requests(), ["/2022/day/1/input"]);
+
+    // The temporary file is cleaned up
+    assert_eq!(fs::read_dir(&dir).unwrap().count(), 1);
+
+    fs::remove_dir_all(dir).unwrap();
+}
+
+#[tokio::test]
+async fn input_rejects_page_instead_of_input() {
+    let stand_in = StandIn::start().await;
+    stand_in.unlock(1, "<!DOCTYPE html>\n<html lang=\"en-us\">\n");
+    let website = Website::new(&stand_in.url(), common::SESSION).unwrap();
+    let dir = common::input_dir("rejects-page");
+
+    let err = input(1, &dir, Some(&website), false).await.unwrap_err();
+    assert!(err.to_string().contains("instead of input"), "{err}");
+    assert!(!dir.join("day_1.txt").exists());
+
+    fs::remove_dir_all(dir).unwrap();
+}
+
+#[tokio::test]
+async fn input_refresh() {
+    let stand_in = StandIn::start().await;
+    stand_in.unlock(1, "1000\n");
+    let website = Website::new(&stand_in.url(), common::SESSION).unwrap();
+    let dir = common::input_dir("refresh");
+
+    // A truncated download from before writes were atomic
+    fs::write(dir.join("day_1.txt"), "10").unwrap();
+
+    let got = input(1, &dir, Some(&website), true).await.unwrap();
+    assert_eq!(got, "1000\n");
+    assert_eq!(fs::read_to_string(dir.join("day_1.txt")).unwrap(), got);
+
+    let err = input(1, &dir, None, true).await.unwrap_err();
+    assert!(err.to_string().contains("no session cookie"), "{err}");
+
+    fs::remove_dir_all(dir).unwrap();
+}
+
+#[tokio::test]
+async fn fetch_all_unlocked_days() {
+    let stand_in = StandIn::start().await;
+    for day in 1..=4 {
+        stand_in.unlock(day, &format!("input {day}\n"));
+    }
+    let website = Website::new(&stand_in.url(), common::SESSION).unwrap();
+    let dir = common::input_dir("fetch-all");
+
+    fs::write(dir.join("day_2.txt"), "cached\n").unwrap();
+
+    // Day 4 is available on the stand-in, but has not unlocked yet
+    let now = website::unlocks_at(3) + Duration::from_secs(60);
+    let interval = Duration::from_millis(50);
+
+    let start = Instant::now();
+    let fetched = fetch_all(&dir, &website, interval, false, now)
+        .await
+        .unwrap();
+    assert_eq!(fetched, [1, 3]);
+    assert!(start.elapsed() >= interval);
+    assert_eq!(
+        stand_in.requests(),
+        ["/2022/day/1/input", "/2022/day/3/input"]
+    );
+
+    assert_eq!(
+        fs::read_to_string(dir.join("day_1.txt")).unwrap(),
+        "input 1\n"
+    );
+    assert_eq!(
+        fs::read_to_string(dir.join("day_2.txt")).unwrap(),
+        "cached\n"
+    );
+    assert_eq!(
+        fs::read_to_string(dir.join("day_3.txt")).unwrap(),
+        "input 3\n"
+    );
+    assert!(!dir.join("day_4.txt").exists());
+
+    // Everything unlocked is cached now
+    let fetched = fetch_all(&dir, &website, interval, false, now)
+        .await
+        .unwrap();
+    assert!(fetched.is_empty());
+    assert_eq!(stand_in.requests().len(), 2);
+
+    fs::remove_dir_all(dir).unwrap();
+}
+
+#[tokio::test]
+async fn fetch_all_reports_failed_days() {
+    let stand_in = StandIn::start().await;
+    stand_in.unlock(1, "input 1\n");
+    let website = Website::new(&stand_in.url(), common::SESSION).unwrap();
+    let dir = common::input_dir("fetch-all-failed");
+
+    let now = website::unlocks_at(2) + Duration::from_secs(60);
+
+    let err = fetch_all(&dir, &website, Duration::ZERO, false, now)
+        .await
+        .unwrap_err();
+    assert!(err.to_string().contains("days [2]"), "{err}");
+    assert!(dir.join("day_1.txt").exists());
+
+    fs::remove_dir_all(dir).unwrap();
+}
@@ -0,0 +1,231 @@
+//! Every solver must return an error rather than panic, whatever its input.
+
+use std::{fs, io::Cursor, time::Duration};
+
+use advent_of_code_2022::{
+    gen,
+    rng::Rng,
+    runner::{self, Input, Outcome},
+    solution_for, variants_for,
+};
+
+/// How long each run may take. Running out of time is not a failure here, only panicking is.
+const TIMEOUT: Duration = Duration::from_secs(1);
+
+/// The number of random inputs to feed each solver.
+const RANDOM_INPUTS: usize = 50;
+
+/// Characters that random inputs are made of, weighted toward those found in puzzle inputs.
+const ALPHABET: &[char] = &[
+    '\n', '\n', '\n', ' ', ' ', ',', '-', '[', ']', '$', '/', '.', ':', '=', '*', '+', '0', '1',
+    '2', '3', '5', '9', 'a', 'b', 'c', 'd', 'e', 'z', 'A', 'B', 'C', 'D', 'E', 'L', 'R', 'S', 'U',
+    'X', 'Y', 'Z', 'é', 'ÿ',
+];
+
+/// Words that random inputs are made of, so that lines get past the first check of a parser.
+const WORDS: &[&str] = &[
+    "move ",
+    " from ",
+    " to ",
+    "$ cd ",
+    "$ ls",
+    "dir ",
+    "..",
+    "addx ",
+    "noop",
+    "Monkey ",
+    "  Starting items: ",
+    "  Operation: new = old ",
+    "  Test: divisible by ",
+    "    If true: throw to monkey ",
+    "    If false: throw to monkey ",
+    "18446744073709551615",
+    "4294967295",
+    "-9223372036854775808",
+    "\n\n",
+];
+
+/// Makes up an input from random characters and words.
+fn random_input(rng: &mut Rng) -> String {
+    let len = rng.below(300);
+    let mut input = String::with_capacity(len);
+
+    while input.len() < len {
+        if rng.below(4) == 0 {
+            input.push_str(rng.choose(WORDS).unwrap());
+        } else {
+            input.push(*rng.choose(ALPHABET).unwrap());
+        }
+    }
+
+    input
+}
+
+/// Reads the example input for `day` from the puzzle description.
+fn example(day: u8) -> String {
+    let path = format!("{}/tests/examples/day{day}.txt", env!("CARGO_MANIFEST_DIR"));
+    fs::read_to_string(path).unwrap()
+}
+
+/// `input` as a reader, for variants that can stream it.
+fn reader(input: &str) -> Input {
+    Input::Reader(Box::new(Cursor::new(input.to_string().into_bytes())))
+}
+
+/// Runs every variant of the solution for `day` on `input`, streaming it too for variants that
+/// can, failing the test if one panics.
+fn assert_no_panic(day: u8, input: &str) {
+    for variant in variants_for(day) {
+        let streamed = variant.stream.is_some().then(|| reader(input));
+
+        for run_input in [Some(Input::from(input.to_string())), streamed]
+            .into_iter()
+            .flatten()
+        {
+            let (outcome, _) = runner::run(
+                &format!("day{day}-{}", variant.name),
+                *variant,
+                run_input,
+                Some(TIMEOUT),
+            );
+
+            assert!(
+                !matches!(outcome, Outcome::Panicked),
+                "day {day} {} variant panicked on input {input:?}",
+                variant.name
+            );
+        }
+    }
+}
+
+/// The days with solutions.
+fn days() -> impl Iterator<Item = u8> {
+    (1..=25).filter(|&day| solution_for(day).is_some())
+}
+
+#[test]
+fn examples_are_solved() {
+    for day in days() {
+        let input = example(day);
+        let variant = variants_for(day)[0];
+        let (outcome, _) = runner::run(
+            &format!("day{day}"),
+            variant,
+            Input::from(input.clone()),
+            Some(TIMEOUT),
+        );
+
+        assert!(outcome.is_ok(), "day {day}: {outcome}");
+
+        if variant.stream.is_some() {
+            let (outcome, _) =
+                runner::run(&format!("day{day}"), variant, reader(&input), Some(TIMEOUT));
+            assert!(outcome.is_ok(), "day {day} streamed: {outcome}");
+        }
+    }
+}
+
+#[test]
+fn empty_input_does_not_panic() {
+    for day in days() {
+        assert_no_panic(day, "");
+        assert_no_panic(day, "\n");
+        assert_no_panic(day, "\n\n\n");
+    }
+}
+
+#[test]
+fn truncated_input_does_not_panic() {
+    for day in days() {
+        let input = example(day);
+
+        for (i, _) in input.char_indices() {
+            assert_no_panic(day, &input[..i]);
+        }
+    }
+}
+
+#[test]
+fn random_input_does_not_panic() {
+    let mut rng = Rng::new(2022);
+
+    for day in days() {
+        for _ in 0..RANDOM_INPUTS {
+            assert_no_panic(day, &random_input(&mut rng));
+        }
+    }
+}
+
+#[test]
+fn variants_agree() {
+    let mut rng = Rng::new(2022);
+
+    for day in days().filter(|&day| variants_for(day).len() > 1) {
+        let generated = [1, 10, 100].map(|size| gen::input(day, &mut rng, size).unwrap());
+
+        for input in [example(day)].into_iter().chain(generated) {
+            let answers: Vec<_> = variants_for(day)
+                .iter()
+                .map(|variant| (variant.solution)(input.clone()).unwrap())
+                .collect();
+
+            assert!(
+                answers.windows(2).all(|pair| pair[0] == pair[1]),
+                "day {day} variants disagree on {input:?}: {answers:?}"
+            );
+        }
+
+        // Short inputs with trailing newlines, which every variant has to read the same way,
+        // failing or not
+        for input in ["abcabc\n", "abcd\n\n", "\n", "ab c\n"] {
+            let answers: Vec<_> = variants_for(day)
+                .iter()
+                .map(|variant| (variant.solution)(input.to_string()).ok())
+                .collect();
+
+            assert!(
+                answers.windows(2).all(|pair| pair[0] == pair[1]),
+                "day {day} variants disagree on {input:?}: {answers:?}"
+            );
+        }
+    }
+}
+
+#[test]
+fn streams_agree() {
+    let mut rng = Rng::new(2022);
+
+    for day in days() {
+        for variant in variants_for(day) {
+            let Some(stream) = variant.stream else {
+                continue;
+            };
+
+            let generated = [1, 10, 100].map(|size| gen::input(day, &mut rng, size).unwrap());
+
+            for input in [example(day)].into_iter().chain(generated) {
+                let streamed = stream(&mut input.as_bytes()).unwrap();
+                let read = (variant.solution)(input.clone()).unwrap();
+
+                assert_eq!(
+                    streamed, read,
+                    "day {day} {} variant streams differently on {input:?}",
+                    variant.name
+                );
+            }
+
+            // Whatever comes after the answers has to be checked as well, so add some junk
+            for junk in ["1", "\nabc", " x", "-", "é"] {
+                let input = format!("{}{junk}\n", example(day).trim_end());
+                let streamed = stream(&mut input.as_bytes()).ok();
+                let read = (variant.solution)(input.clone()).ok();
+
+                assert_eq!(
+                    streamed, read,
+                    "day {day} {} variant streams differently on {input:?}",
+                    variant.name
+                );
+            }
+        }
+    }
+}
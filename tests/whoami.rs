@@ -0,0 +1,23 @@
+mod common;
+
+use advent_of_code_2022::website::Website;
+
+use common::StandIn;
+
+#[tokio::test]
+async fn whoami_valid_session() {
+    let stand_in = StandIn::start().await;
+    let website = Website::new(&stand_in.url(), common::SESSION).unwrap();
+
+    assert_eq!(website.whoami().await.unwrap(), common::USER);
+    assert_eq!(stand_in.requests(), ["/2022"]);
+}
+
+#[tokio::test]
+async fn whoami_invalid_session() {
+    let stand_in = StandIn::start().await;
+    let website = Website::new(&stand_in.url(), "expired").unwrap();
+
+    let err = website.whoami().await.unwrap_err();
+    assert!(err.to_string().contains("not accepted"), "{err}");
+}